@@ -1,3 +1,9 @@
+pub mod backend;
 pub mod components;
+pub mod events;
+pub mod geometry;
+pub mod kinematic;
+pub mod layers;
 pub mod prelude;
+pub mod queries;
 pub mod resolution_utils;
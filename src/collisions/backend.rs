@@ -0,0 +1,117 @@
+use std::{fmt::Debug, hash::Hash};
+
+use amethyst::ecs::Entity;
+use nalgebra::{Isometry2, Point2, Unit, Vector2};
+
+use super::{
+    events::CollisionEvent,
+    geometry::{CollisionGroups, CollisionShape, Contact, QueryType, RayHit, SweepHit},
+};
+
+#[cfg(feature = "ncollide2d-backend")]
+mod ncollide;
+
+#[cfg(feature = "ncollide2d-backend")]
+pub use self::ncollide::Ncollide2dBackend;
+
+/// The collision world resource, using the backend selected by cargo
+/// features.
+#[cfg(feature = "ncollide2d-backend")]
+pub type PhysicsWorld = Ncollide2dBackend;
+
+#[cfg(not(feature = "ncollide2d-backend"))]
+compile_error!("a physics backend must be selected with a feature, such as \"ncollide2d-backend\"");
+
+/// Handle of a collision object in the `PhysicsWorld`.
+pub type BackendHandle = <PhysicsWorld as PhysicsBackend>::Handle;
+
+/// A read-only view of a collision object.
+pub struct ObjectRef<'a> {
+    pub entity: Entity,
+    pub position: &'a Isometry2<f32>,
+    pub shape: &'a CollisionShape,
+    pub collision_groups: &'a CollisionGroups,
+}
+
+/// The collision engine behind `PhysicsWorld`.
+///
+/// Everything outside of a backend implementation talks to the collision
+/// engine through this trait, so another engine can be tried out by adding an
+/// implementation and selecting it with a cargo feature. Shapes, collision
+/// groups and query results are the engine-neutral types from `geometry`,
+/// which each backend converts to and from its engine's own.
+pub trait PhysicsBackend: Default + Send + Sync + 'static {
+    type Handle: Copy + Eq + Hash + Debug + Send + Sync + 'static;
+
+    fn add(
+        &mut self,
+        position: Isometry2<f32>,
+        shape: CollisionShape,
+        collision_groups: CollisionGroups,
+        query_type: QueryType,
+        entity: Entity,
+    ) -> Self::Handle;
+    fn remove(&mut self, handle: Self::Handle);
+
+    /// The setters panic if `handle` doesn't refer to an object in this
+    /// world.
+    fn set_position(&mut self, handle: Self::Handle, position: Isometry2<f32>);
+    fn set_shape(&mut self, handle: Self::Handle, shape: CollisionShape);
+    fn set_collision_groups(&mut self, handle: Self::Handle, collision_groups: CollisionGroups);
+    fn set_query_type(&mut self, handle: Self::Handle, query_type: QueryType);
+
+    /// Recomputes contacts and proximities after objects were added, removed
+    /// or changed, and replaces the events returned by `collision_events`.
+    fn update(&mut self);
+
+    fn object(&self, handle: Self::Handle) -> Option<ObjectRef<'_>>;
+    fn objects(&self) -> Vec<Self::Handle>;
+
+    /// Every object `shape` would hit moving from `position` along
+    /// `direction` for up to `max_distance`, in no particular order. Sensors
+    /// are skipped.
+    fn sweep_test(
+        &self,
+        shape: &CollisionShape,
+        position: &Isometry2<f32>,
+        direction: &Unit<Vector2<f32>>,
+        max_distance: f32,
+        collision_groups: &CollisionGroups,
+    ) -> Vec<(Self::Handle, SweepHit)>;
+    fn interferences_with_ray(
+        &self,
+        origin: &Point2<f32>,
+        direction: &Unit<Vector2<f32>>,
+        max_toi: f32,
+        collision_groups: &CollisionGroups,
+    ) -> Vec<(Self::Handle, RayHit)>;
+    /// Objects whose shapes intersect `shape` placed at `position`.
+    fn intersections_with_shape(
+        &self,
+        shape: &CollisionShape,
+        position: &Isometry2<f32>,
+        collision_groups: &CollisionGroups,
+    ) -> Vec<Self::Handle>;
+    /// The contact between two shapes, if they're within `prediction` of
+    /// each other.
+    fn contact(
+        &self,
+        position1: &Isometry2<f32>,
+        shape1: &CollisionShape,
+        position2: &Isometry2<f32>,
+        shape2: &CollisionShape,
+        prediction: f32,
+    ) -> Option<Contact>;
+
+    /// Solid objects in contact with `handle`, as of the last `update`.
+    fn contacts_with(&self, handle: Self::Handle) -> Vec<Self::Handle>;
+    /// Sensors and objects overlapping with `handle`, as of the last
+    /// `update`.
+    fn proximities_with(&self, handle: Self::Handle) -> Vec<Self::Handle>;
+    /// Every pair of objects in contact along with their contact points.
+    fn contact_pairs(&self) -> Vec<(Self::Handle, Self::Handle, Vec<Contact>)>;
+    /// Contacts and proximities which started or stopped during the last
+    /// `update`. Solid objects only start touching once one of their
+    /// contacts has a depth, however close they were before.
+    fn collision_events(&self) -> &[CollisionEvent];
+}
@@ -0,0 +1,415 @@
+use std::collections::HashSet;
+
+use amethyst::ecs::Entity;
+use nalgebra::{Isometry2, Point2, Unit, Vector2};
+use ncollide2d::{
+    pipeline::{
+        self,
+        object::{CollisionObject, CollisionObjectSlabHandle},
+        world::CollisionWorld,
+        GeometricQueryType,
+    },
+    query::{self, Proximity, Ray},
+    shape::{Ball, ConvexPolygon, Cuboid, ShapeHandle},
+};
+
+use super::{ObjectRef, PhysicsBackend};
+use crate::collisions::{
+    events::{CollisionEvent, CollisionEventKind},
+    geometry::{CollisionGroups, CollisionShape, Contact, QueryType, RayHit, SweepHit},
+};
+
+/// What's kept alongside each ncollide2d object, so that it can be viewed in
+/// terms of the backend-neutral types.
+struct ObjectData {
+    entity: Entity,
+    shape: CollisionShape,
+    collision_groups: CollisionGroups,
+}
+
+pub struct Ncollide2dBackend {
+    world: CollisionWorld<f32, ObjectData>,
+    /// Pairs of solid objects which were touching as of the last `update`.
+    touching: HashSet<(CollisionObjectSlabHandle, CollisionObjectSlabHandle)>,
+    events: Vec<CollisionEvent>,
+}
+
+impl Default for Ncollide2dBackend {
+    fn default() -> Self {
+        Ncollide2dBackend {
+            world: CollisionWorld::new(0.02),
+            touching: HashSet::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+fn shape_handle(shape: &CollisionShape) -> ShapeHandle<f32> {
+    match shape {
+        CollisionShape::Cuboid { half_extents } => ShapeHandle::new(Cuboid::new(*half_extents)),
+        CollisionShape::Ball { radius } => ShapeHandle::new(Ball::new(*radius)),
+        CollisionShape::ConvexPolygon { points } => ShapeHandle::new(
+            ConvexPolygon::try_from_points(points)
+                .expect("expected convex polygon to have an area"),
+        ),
+    }
+}
+
+fn ncollide_groups(groups: &CollisionGroups) -> pipeline::CollisionGroups {
+    let mut converted = pipeline::CollisionGroups::new()
+        .with_membership(&groups.membership())
+        .with_whitelist(&groups.whitelist());
+    if !groups.self_interaction() {
+        converted.disable_self_interaction();
+    }
+    converted
+}
+
+fn ncollide_query_type(query_type: QueryType) -> GeometricQueryType<f32> {
+    match query_type {
+        QueryType::Contacts { prediction } => GeometricQueryType::Contacts(prediction, 0.0),
+        QueryType::Proximity => GeometricQueryType::Proximity(0.0),
+    }
+}
+
+fn contact(contact: query::Contact<f32>) -> Contact {
+    Contact {
+        world1: contact.world1,
+        world2: contact.world2,
+        normal: contact.normal,
+        depth: contact.depth,
+    }
+}
+
+impl Ncollide2dBackend {
+    fn object_mut(
+        &mut self,
+        handle: CollisionObjectSlabHandle,
+    ) -> &mut CollisionObject<f32, ObjectData> {
+        self.world
+            .objects
+            .get_mut(handle)
+            .expect("expected CollisionWorld to have object")
+    }
+
+    fn entity(&self, handle: CollisionObjectSlabHandle) -> Option<Entity> {
+        self.world.objects.get(handle).map(|obj| obj.data().entity)
+    }
+
+    /// Solid objects with at least one contact which has a depth, rather
+    /// than only being within their prediction distance.
+    fn touching_pairs(&self) -> HashSet<(CollisionObjectSlabHandle, CollisionObjectSlabHandle)> {
+        self.world
+            .contact_pairs(true)
+            .filter(|(_, _, _, manifold)| {
+                manifold
+                    .contacts()
+                    .any(|tracked| tracked.contact.depth >= 0.0)
+            })
+            .map(|(handle1, handle2, _, _)| {
+                // pairs aren't always reported in the same order.
+                if handle1.0 <= handle2.0 {
+                    (handle1, handle2)
+                } else {
+                    (handle2, handle1)
+                }
+            })
+            .collect()
+    }
+}
+
+impl PhysicsBackend for Ncollide2dBackend {
+    type Handle = CollisionObjectSlabHandle;
+
+    fn add(
+        &mut self,
+        position: Isometry2<f32>,
+        shape: CollisionShape,
+        collision_groups: CollisionGroups,
+        query_type: QueryType,
+        entity: Entity,
+    ) -> Self::Handle {
+        let (handle, _object) = self.world.add(
+            position,
+            shape_handle(&shape),
+            ncollide_groups(&collision_groups),
+            ncollide_query_type(query_type),
+            ObjectData {
+                entity,
+                shape,
+                collision_groups,
+            },
+        );
+        handle
+    }
+
+    fn remove(&mut self, handle: Self::Handle) {
+        self.world.remove(&[handle]);
+    }
+
+    fn set_position(&mut self, handle: Self::Handle, position: Isometry2<f32>) {
+        self.object_mut(handle).set_position(position);
+    }
+
+    fn set_shape(&mut self, handle: Self::Handle, shape: CollisionShape) {
+        let object = self.object_mut(handle);
+        object.set_shape(shape_handle(&shape));
+        object.data_mut().shape = shape;
+    }
+
+    fn set_collision_groups(&mut self, handle: Self::Handle, collision_groups: CollisionGroups) {
+        let object = self.object_mut(handle);
+        object.set_collision_groups(ncollide_groups(&collision_groups));
+        object.data_mut().collision_groups = collision_groups;
+    }
+
+    fn set_query_type(&mut self, handle: Self::Handle, query_type: QueryType) {
+        self.object_mut(handle)
+            .set_query_type(ncollide_query_type(query_type));
+    }
+
+    fn update(&mut self) {
+        self.world.update();
+
+        let touching = self.touching_pairs();
+        // events can refer to objects which have since been removed, so
+        // silently skip anything we can't find.
+        let event = |(handle1, handle2), kind| {
+            Some(CollisionEvent {
+                a: self.entity(handle1)?,
+                b: self.entity(handle2)?,
+                kind,
+            })
+        };
+        let started = touching
+            .difference(&self.touching)
+            .filter_map(|&pair| event(pair, CollisionEventKind::Started));
+        let stopped = self
+            .touching
+            .difference(&touching)
+            .filter_map(|&pair| event(pair, CollisionEventKind::Stopped));
+        let proximity_events = self.world.proximity_events().iter().filter_map(|e| {
+            let was_intersecting = e.prev_status == Proximity::Intersecting;
+            let is_intersecting = e.new_status == Proximity::Intersecting;
+            let pair = (e.collider1, e.collider2);
+            match (was_intersecting, is_intersecting) {
+                (false, true) => event(pair, CollisionEventKind::Started),
+                (true, false) => event(pair, CollisionEventKind::Stopped),
+                _ => None,
+            }
+        });
+        let events = started.chain(stopped).chain(proximity_events).collect();
+
+        self.events = events;
+        self.touching = touching;
+    }
+
+    fn object(&self, handle: Self::Handle) -> Option<ObjectRef<'_>> {
+        self.world.objects.get(handle).map(|obj| ObjectRef {
+            entity: obj.data().entity,
+            position: obj.position(),
+            shape: &obj.data().shape,
+            collision_groups: &obj.data().collision_groups,
+        })
+    }
+
+    fn objects(&self) -> Vec<Self::Handle> {
+        self.world.objects.iter().map(|(handle, _)| handle).collect()
+    }
+
+    fn sweep_test(
+        &self,
+        shape: &CollisionShape,
+        position: &Isometry2<f32>,
+        direction: &Unit<Vector2<f32>>,
+        max_distance: f32,
+        collision_groups: &CollisionGroups,
+    ) -> Vec<(Self::Handle, SweepHit)> {
+        let shape = shape_handle(shape);
+        let groups = ncollide_groups(collision_groups);
+        self.world
+            .sweep_test(&*shape, position, direction, max_distance, &groups)
+            // sensors never block movement.
+            .filter(|(handle, _)| match self.world.objects.get(*handle) {
+                Some(obj) => !obj.query_type().is_proximity_query(),
+                None => false,
+            })
+            .map(|(handle, toi)| {
+                let hit = SweepHit {
+                    toi: toi.toi,
+                    normal1: toi.normal1,
+                    normal2: toi.normal2,
+                };
+                (handle, hit)
+            })
+            .collect()
+    }
+
+    fn interferences_with_ray(
+        &self,
+        origin: &Point2<f32>,
+        direction: &Unit<Vector2<f32>>,
+        max_toi: f32,
+        collision_groups: &CollisionGroups,
+    ) -> Vec<(Self::Handle, RayHit)> {
+        let ray = Ray::new(*origin, direction.into_inner());
+        let groups = ncollide_groups(collision_groups);
+        self.world
+            .interferences_with_ray(&ray, max_toi, &groups)
+            .map(|(handle, _, hit)| {
+                let hit = RayHit {
+                    toi: hit.toi,
+                    normal: hit.normal,
+                };
+                (handle, hit)
+            })
+            .collect()
+    }
+
+    fn intersections_with_shape(
+        &self,
+        shape: &CollisionShape,
+        position: &Isometry2<f32>,
+        collision_groups: &CollisionGroups,
+    ) -> Vec<Self::Handle> {
+        let shape = shape_handle(shape);
+        let groups = ncollide_groups(collision_groups);
+        let aabb = shape.aabb(position);
+        self.world
+            .interferences_with_aabb(&aabb, &groups)
+            .filter(|(_, obj)| {
+                let proximity =
+                    query::proximity(position, &*shape, obj.position(), &**obj.shape(), 0.0);
+                proximity == Proximity::Intersecting
+            })
+            .map(|(handle, _)| handle)
+            .collect()
+    }
+
+    fn contact(
+        &self,
+        position1: &Isometry2<f32>,
+        shape1: &CollisionShape,
+        position2: &Isometry2<f32>,
+        shape2: &CollisionShape,
+        prediction: f32,
+    ) -> Option<Contact> {
+        let (shape1, shape2) = (shape_handle(shape1), shape_handle(shape2));
+        query::contact(position1, &*shape1, position2, &*shape2, prediction).map(contact)
+    }
+
+    fn contacts_with(&self, handle: Self::Handle) -> Vec<Self::Handle> {
+        self.world
+            .contacts_with(handle, true)
+            .into_iter()
+            .flatten()
+            .map(|(handle1, handle2, _algo, _manifold)| {
+                if handle1 == handle {
+                    handle2
+                } else {
+                    handle1
+                }
+            })
+            .collect()
+    }
+
+    fn proximities_with(&self, handle: Self::Handle) -> Vec<Self::Handle> {
+        self.world
+            .proximities_with(handle, true)
+            .into_iter()
+            .flatten()
+            .filter(|(_, _, _, prox)| *prox == Proximity::Intersecting)
+            .map(|(handle1, handle2, _, _)| if handle1 == handle { handle2 } else { handle1 })
+            .collect()
+    }
+
+    fn contact_pairs(&self) -> Vec<(Self::Handle, Self::Handle, Vec<Contact>)> {
+        self.world
+            .contact_pairs(true)
+            .map(|(handle1, handle2, _algo, manifold)| {
+                let contacts = manifold
+                    .contacts()
+                    .map(|tracked| contact(tracked.contact))
+                    .collect();
+                (handle1, handle2, contacts)
+            })
+            .collect()
+    }
+
+    fn collision_events(&self) -> &[CollisionEvent] {
+        &self.events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::ecs::prelude::*;
+    use nalgebra::{Isometry2, Vector2};
+
+    use super::Ncollide2dBackend;
+    use crate::collisions::{
+        backend::PhysicsBackend,
+        events::CollisionEventKind,
+        geometry::{CollisionGroups, CollisionShape, QueryType},
+    };
+
+    #[test]
+    fn finds_intersecting_proximities() {
+        let mut ecs = World::new();
+        let (sensor, near, far) = (
+            ecs.create_entity().build(),
+            ecs.create_entity().build(),
+            ecs.create_entity().build(),
+        );
+        let mut world = Ncollide2dBackend::default();
+        let mut add = |x, query_type, entity| {
+            world.add(
+                Isometry2::translation(x, 0.0),
+                CollisionShape::cuboid(Vector2::new(8.0, 8.0)),
+                CollisionGroups::new(),
+                query_type,
+                entity,
+            )
+        };
+        let solid = QueryType::Contacts { prediction: 0.0 };
+        let sensor = add(0.0, QueryType::Proximity, sensor);
+        let near = add(10.0, solid, near);
+        add(100.0, solid, far);
+        world.update();
+        assert_eq!(world.proximities_with(sensor), vec![near]);
+        assert_eq!(world.proximities_with(near), vec![sensor]);
+    }
+
+    #[test]
+    fn starts_contact_on_touch_rather_than_within_prediction() {
+        let mut ecs = World::new();
+        let (a, b) = (ecs.create_entity().build(), ecs.create_entity().build());
+        let mut world = Ncollide2dBackend::default();
+        let mut add = |x, entity| {
+            world.add(
+                Isometry2::translation(x, 0.0),
+                CollisionShape::cuboid(Vector2::new(8.0, 8.0)),
+                CollisionGroups::new(),
+                QueryType::Contacts { prediction: 4.0 },
+                entity,
+            )
+        };
+        add(0.0, a);
+        let moving = add(18.0, b);
+        let mut step = |x| {
+            world.set_position(moving, Isometry2::translation(x, 0.0));
+            world.update();
+            world
+                .collision_events()
+                .iter()
+                .map(|e| e.kind)
+                .collect::<Vec<_>>()
+        };
+
+        // 2 units apart, well within the prediction distance.
+        assert_eq!(step(18.0), vec![]);
+        assert_eq!(step(15.0), vec![CollisionEventKind::Started]);
+        assert_eq!(step(14.0), vec![]);
+        assert_eq!(step(18.0), vec![CollisionEventKind::Stopped]);
+    }
+}
@@ -1,25 +1,91 @@
 use amethyst::ecs::prelude::*;
-use ncollide2d::{
-    pipeline::{object::CollisionObjectSlabHandle, CollisionGroups, GeometricQueryType},
-    shape::ShapeHandle,
-};
+use nalgebra::{Unit, Vector2};
 use specs_derive::Component;
 
+use super::{
+    backend::BackendHandle,
+    geometry::{CollisionGroups, CollisionShape, QueryType},
+};
+use crate::world::Surface;
+
 #[derive(Clone)]
 pub struct CollisionPresence {
-    pub shape: ShapeHandle<f32>,
+    pub shape: CollisionShape,
     pub collision_groups: CollisionGroups,
-    pub query_type: GeometricQueryType<f32>,
+    pub query_type: QueryType,
 }
 
 impl Component for CollisionPresence {
     type Storage = FlaggedStorage<Self, VecStorage<Self>>;
 }
 
+impl CollisionPresence {
+    /// A presence which reports overlaps but never blocks movement.
+    pub fn sensor(shape: CollisionShape, collision_groups: CollisionGroups) -> Self {
+        CollisionPresence {
+            shape,
+            collision_groups,
+            query_type: QueryType::Proximity,
+        }
+    }
+
+    /// The shape stretched by a `Transform`'s scale.
+    pub fn scaled_shape(&self, scale: Vector2<f32>) -> CollisionShape {
+        self.shape.scaled(scale)
+    }
+}
+
 #[derive(Component)]
 #[storage(VecStorage)]
-pub struct Ncollide2dHandle(pub(crate) CollisionObjectSlabHandle);
+pub struct PhysicsHandle(pub(crate) BackendHandle);
 
-#[derive(Default, Debug, Component)]
-#[storage(NullStorage)]
-pub struct HasGravity;
+#[derive(Copy, Clone, Debug, Component)]
+#[storage(DenseVecStorage)]
+pub struct HasGravity {
+    /// Multiplier on the global gravity acceleration.
+    pub scale: f32,
+    /// Overrides the global terminal velocity.
+    pub max_fall_speed: Option<f32>,
+}
+
+impl Default for HasGravity {
+    fn default() -> Self {
+        HasGravity {
+            scale: 1.0,
+            max_fall_speed: None,
+        }
+    }
+}
+
+/// What an entity is standing on, recomputed once per frame by
+/// `GroundProbeSystem`.
+#[derive(Clone, Debug, Component)]
+#[storage(DenseVecStorage)]
+pub struct GroundState {
+    pub grounded: bool,
+    /// Normal of the ground surface, pointing away from the ground.
+    pub normal: Option<Unit<Vector2<f32>>>,
+    pub entity: Option<Entity>,
+    /// The `Surface` of the ground entity, if it has one.
+    pub surface: Option<Surface>,
+    /// Seconds since the entity was last grounded, or `0.0` while grounded.
+    pub time_since_grounded: f32,
+    /// Fastest downward speed since the entity was last grounded.
+    pub fall_speed: f32,
+    /// The `fall_speed` the entity landed with, on the frame it landed.
+    pub landing_speed: Option<f32>,
+}
+
+impl Default for GroundState {
+    fn default() -> Self {
+        GroundState {
+            grounded: false,
+            normal: None,
+            entity: None,
+            surface: None,
+            time_since_grounded: 0.0,
+            fall_speed: 0.0,
+            landing_speed: None,
+        }
+    }
+}
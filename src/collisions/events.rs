@@ -0,0 +1,19 @@
+use amethyst::ecs::Entity;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CollisionEventKind {
+    Started,
+    Stopped,
+}
+
+/// Sent whenever two collision objects start or stop touching (for solid
+/// presences) or overlapping (for sensors).
+///
+/// Events are not ordered by entity: a system interested in a particular
+/// component should check both `a` and `b`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CollisionEvent {
+    pub a: Entity,
+    pub b: Entity,
+    pub kind: CollisionEventKind,
+}
@@ -0,0 +1,219 @@
+//! Shapes, collision groups and query results shared by every
+//! `PhysicsBackend`, so that nothing outside of a backend depends on the
+//! collision engine behind it.
+
+use log::warn;
+use nalgebra::{Point2, Unit, Vector2};
+
+/// A collision shape, relative to its object's position.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CollisionShape {
+    /// A rectangle centered on the origin.
+    Cuboid {
+        half_extents: Vector2<f32>,
+    },
+    Ball {
+        radius: f32,
+    },
+    /// The convex hull of `points`.
+    ConvexPolygon {
+        points: Vec<Point2<f32>>,
+    },
+}
+
+impl CollisionShape {
+    pub fn cuboid(half_extents: Vector2<f32>) -> Self {
+        CollisionShape::Cuboid { half_extents }
+    }
+
+    /// The shape stretched by a `Transform`'s scale.
+    ///
+    /// Balls can't be stretched unevenly, so they grow to fit the larger
+    /// axis.
+    pub fn scaled(&self, scale: Vector2<f32>) -> Self {
+        if scale == Vector2::new(1.0, 1.0) {
+            return self.clone();
+        }
+        let abs_scale = scale.abs();
+        match self {
+            CollisionShape::Cuboid { half_extents } => {
+                CollisionShape::cuboid(half_extents.component_mul(&abs_scale))
+            }
+            CollisionShape::Ball { radius } => CollisionShape::Ball {
+                radius: radius * abs_scale.max(),
+            },
+            CollisionShape::ConvexPolygon { .. } if scale.x == 0.0 || scale.y == 0.0 => {
+                warn!("scale {},{} collapses a convex polygon", scale.x, scale.y);
+                self.clone()
+            }
+            CollisionShape::ConvexPolygon { points } => CollisionShape::ConvexPolygon {
+                points: points
+                    .iter()
+                    .map(|p| Point2::from(p.coords.component_mul(&scale)))
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// Which collision groups an object is a member of, and which groups it
+/// interacts with. Two objects interact when each is a member of a group the
+/// other interacts with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CollisionGroups {
+    membership: u32,
+    whitelist: u32,
+    self_interaction: bool,
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        CollisionGroups::new()
+    }
+}
+
+impl CollisionGroups {
+    /// Group ids go from 0 up to, but not including, this.
+    pub const MAX_GROUPS: usize = 30;
+    const ALL_GROUPS: u32 = (1 << Self::MAX_GROUPS) - 1;
+
+    /// A member of every group, interacting with every group.
+    pub fn new() -> Self {
+        CollisionGroups {
+            membership: Self::ALL_GROUPS,
+            whitelist: Self::ALL_GROUPS,
+            self_interaction: true,
+        }
+    }
+
+    fn mask(groups: &[usize]) -> u32 {
+        groups.iter().fold(0, |mask, &group| {
+            assert!(
+                group < Self::MAX_GROUPS,
+                "collision group {} is too large",
+                group
+            );
+            mask | (1 << group)
+        })
+    }
+
+    fn groups(mask: u32) -> Vec<usize> {
+        (0..Self::MAX_GROUPS)
+            .filter(|group| mask & (1 << group) != 0)
+            .collect()
+    }
+
+    pub fn with_membership(mut self, groups: &[usize]) -> Self {
+        self.membership = Self::mask(groups);
+        self
+    }
+
+    pub fn with_whitelist(mut self, groups: &[usize]) -> Self {
+        self.whitelist = Self::mask(groups);
+        self
+    }
+
+    pub fn disable_self_interaction(&mut self) {
+        self.self_interaction = false;
+    }
+
+    pub fn membership(&self) -> Vec<usize> {
+        Self::groups(self.membership)
+    }
+
+    pub fn whitelist(&self) -> Vec<usize> {
+        Self::groups(self.whitelist)
+    }
+
+    pub fn self_interaction(&self) -> bool {
+        self.self_interaction
+    }
+
+    pub fn is_member_of(&self, group: usize) -> bool {
+        group < Self::MAX_GROUPS && self.membership & (1 << group) != 0
+    }
+}
+
+/// How an object takes part in collisions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum QueryType {
+    /// A solid object, with contacts computed against objects within
+    /// `prediction` of it.
+    Contacts { prediction: f32 },
+    /// A sensor, which only reports overlaps and never blocks movement.
+    Proximity,
+}
+
+/// Where a swept shape first hits an object.
+#[derive(Copy, Clone, Debug)]
+pub struct SweepHit {
+    /// How far the shape moved before the hit.
+    pub toi: f32,
+    /// Normal of the swept shape at the hit.
+    pub normal1: Unit<Vector2<f32>>,
+    /// Normal of the object which was hit.
+    pub normal2: Unit<Vector2<f32>>,
+}
+
+/// Where a ray first hits an object.
+#[derive(Copy, Clone, Debug)]
+pub struct RayHit {
+    /// How far along the ray the hit is, in multiples of its direction.
+    pub toi: f32,
+    /// Normal of the object's surface at the hit.
+    pub normal: Vector2<f32>,
+}
+
+/// The closest points of two shapes, which are touching when `depth` is
+/// positive or zero.
+#[derive(Copy, Clone, Debug)]
+pub struct Contact {
+    /// The point of the first shape furthest into the second.
+    pub world1: Point2<f32>,
+    /// The point of the second shape furthest into the first.
+    pub world2: Point2<f32>,
+    /// Points from the first shape towards the second.
+    pub normal: Unit<Vector2<f32>>,
+    /// How far the shapes overlap. Negative if they're apart.
+    pub depth: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Vector2};
+
+    use super::{CollisionGroups, CollisionShape};
+
+    #[test]
+    fn scales_shapes() {
+        let cuboid = CollisionShape::cuboid(Vector2::new(8.0, 4.0));
+        assert_eq!(
+            cuboid.scaled(Vector2::new(-2.0, 0.5)),
+            CollisionShape::cuboid(Vector2::new(16.0, 2.0))
+        );
+        let ball = CollisionShape::Ball { radius: 3.0 };
+        assert_eq!(
+            ball.scaled(Vector2::new(1.0, 2.0)),
+            CollisionShape::Ball { radius: 6.0 }
+        );
+        let polygon = CollisionShape::ConvexPolygon {
+            points: vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(1.0, 0.0),
+                Point2::new(0.0, 1.0),
+            ],
+        };
+        assert_eq!(polygon.scaled(Vector2::new(2.0, 0.0)), polygon);
+    }
+
+    #[test]
+    fn lists_groups() {
+        let groups = CollisionGroups::new()
+            .with_membership(&[1])
+            .with_whitelist(&[0, 3]);
+        assert_eq!(groups.membership(), vec![1]);
+        assert_eq!(groups.whitelist(), vec![0, 3]);
+        assert!(groups.is_member_of(1));
+        assert!(!groups.is_member_of(0));
+    }
+}
@@ -0,0 +1,259 @@
+use amethyst::ecs::Entity;
+use log::debug;
+use nalgebra::{Isometry2, Point2, Unit, Vector2};
+
+use super::{
+    backend::PhysicsBackend,
+    geometry::{CollisionGroups, CollisionShape},
+    prelude::IsometryExt,
+    resolution_utils::contact_in_direction_with_shape,
+};
+
+const MAX_ITERATIONS: u32 = 5;
+
+/// An obstacle hit during `move_and_slide`.
+#[derive(Clone, Debug)]
+pub struct Hit {
+    /// The entity which was hit.
+    pub entity: Entity,
+    /// Contact point on the obstacle.
+    pub point: Point2<f32>,
+    /// Normal of the obstacle's surface at `point`, pointing away from it.
+    pub normal: Unit<Vector2<f32>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MoveResult {
+    pub isometry: Isometry2<f32>,
+    /// The velocity left after removing every component pointing into an
+    /// obstacle.
+    pub velocity: Vector2<f32>,
+    pub hits: Vec<Hit>,
+}
+
+/// Moves `shape` from `isometry` at `velocity` for `delta_seconds`, stopping
+/// at obstacles and sliding along them.
+///
+/// The moving shape doesn't need to be part of `world`, but if it is, its
+/// `groups` must exclude itself.
+pub fn move_and_slide<B: PhysicsBackend>(
+    world: &B,
+    shape: &CollisionShape,
+    mut isometry: Isometry2<f32>,
+    mut velocity: Vector2<f32>,
+    delta_seconds: f32,
+    groups: &CollisionGroups,
+) -> MoveResult {
+    let mut hits = Vec::new();
+    if velocity == Vector2::zeros() {
+        return MoveResult {
+            isometry,
+            velocity,
+            hits,
+        };
+    }
+    let mut direction = Unit::<Vector2<f32>>::new_normalize(velocity);
+    let mut maximum_distance = velocity.magnitude() * delta_seconds;
+
+    let all_clear = world
+        .sweep_test(shape, &isometry, &direction, maximum_distance, groups)
+        .is_empty();
+    if all_clear {
+        isometry = isometry.prepend_movement(direction, maximum_distance);
+        return MoveResult {
+            isometry,
+            velocity,
+            hits,
+        };
+    }
+    debug!(
+        "---- calculating collisions for object at {},{} with velocity {},{}",
+        isometry.translation.x, isometry.translation.y, velocity.x, velocity.y
+    );
+    let mut remaining_time = delta_seconds;
+    let mut iterations_left = MAX_ITERATIONS;
+    let all_clear = loop {
+        let sweep = world.sweep_test(shape, &isometry, &direction, maximum_distance, groups);
+        let nearest = sweep
+            .into_iter()
+            .filter_map(|(obj, toi)| {
+                let effected_by_toi = isometry.prepend_movement(direction, toi.toi);
+                let obj = world.object(obj)?;
+
+                let contact2 = contact_in_direction_with_shape(
+                    world,
+                    &effected_by_toi,
+                    shape,
+                    &obj,
+                    direction,
+                )?;
+
+                Some((obj, toi, contact2))
+            })
+            .min_by(|(_, toi1, _), (_, toi2, _)| toi1.toi.partial_cmp(&toi2.toi).unwrap());
+
+        let (obj, toi, contact_at_depth) = match nearest {
+            Some(v) => v,
+            None => break true,
+        };
+
+        debug!(
+            "found a collision for {},{} moving {} in {},{}! Collision is with {},{} with normal1: {},{}, normal2: {},{} (full: {:?})",
+            isometry.translation.x,
+            isometry.translation.y,
+            maximum_distance,
+            direction.as_ref().x,
+            direction.as_ref().y,
+            obj.position.translation.x,
+            obj.position.translation.y,
+            toi.normal1.x,
+            toi.normal1.y,
+            toi.normal2.x,
+            toi.normal2.y,
+            toi,
+        );
+
+        isometry = isometry.prepend_movement(direction, toi.toi);
+        remaining_time -= toi.toi / velocity.magnitude();
+        iterations_left -= 1;
+        hits.push(Hit {
+            entity: obj.entity,
+            point: contact_at_depth.world2,
+            normal: -contact_at_depth.normal,
+        });
+        // kill velocity towards the obstacle.
+        let old_vel = velocity;
+        velocity -= velocity.dot(contact_at_depth.normal.as_ref()) * contact_at_depth.normal.as_ref();
+        debug!(
+            "velocity change: {},{} -> {},{}",
+            old_vel.x, old_vel.y, velocity.x, velocity.y
+        );
+        direction = Unit::new_normalize(velocity); // note: will be NaN if velocity == 0.0.
+        maximum_distance = velocity.magnitude() * delta_seconds;
+        if remaining_time <= 0.0 || iterations_left == 0 || maximum_distance == 0.0 {
+            break false;
+        }
+        debug!(
+            "more movement left! new direction is {},{}",
+            direction.as_ref().x,
+            direction.as_ref().y
+        );
+    };
+    // do the last bit of movement if we stopped b/c of remaining_time
+    // or iterations_left.
+    if all_clear {
+        isometry.translation.vector += velocity * remaining_time;
+    }
+    MoveResult {
+        isometry,
+        velocity,
+        hits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::ecs::prelude::*;
+    use nalgebra::{Isometry2, Vector2};
+
+    use super::move_and_slide;
+    use crate::collisions::{
+        backend::{PhysicsBackend, PhysicsWorld},
+        geometry::{CollisionGroups, CollisionShape, QueryType},
+    };
+
+    /// Returns the world along with the floor's entity.
+    fn world_with_floor() -> (PhysicsWorld, Entity) {
+        let floor = World::new().create_entity().build();
+        let mut world = PhysicsWorld::default();
+        world.add(
+            Isometry2::translation(0.0, 0.0),
+            CollisionShape::cuboid(Vector2::new(64.0, 8.0)),
+            CollisionGroups::new(),
+            QueryType::Contacts { prediction: 0.0 },
+            floor,
+        );
+        world.update();
+        (world, floor)
+    }
+
+    fn block() -> CollisionShape {
+        CollisionShape::cuboid(Vector2::new(8.0, 8.0))
+    }
+
+    #[test]
+    fn moves_freely_without_obstacles() {
+        let (world, _) = world_with_floor();
+        let result = move_and_slide(
+            &world,
+            &block(),
+            Isometry2::translation(0.0, 50.0),
+            Vector2::new(10.0, 0.0),
+            0.5,
+            &CollisionGroups::new(),
+        );
+        assert_eq!(result.isometry.translation.vector, Vector2::new(5.0, 50.0));
+        assert_eq!(result.velocity, Vector2::new(10.0, 0.0));
+        assert!(result.hits.is_empty());
+    }
+
+    #[test]
+    fn stops_on_floor() {
+        let (world, floor) = world_with_floor();
+        let result = move_and_slide(
+            &world,
+            &block(),
+            Isometry2::translation(0.0, 20.0),
+            Vector2::new(0.0, -100.0),
+            0.1,
+            &CollisionGroups::new(),
+        );
+        assert!((result.isometry.translation.y - 16.0).abs() < 0.01);
+        assert!(result.velocity.y.abs() < 0.001);
+        assert_eq!(result.hits.len(), 1);
+        assert_eq!(result.hits[0].entity, floor);
+        assert!(result.hits[0].normal.y > 0.99);
+    }
+
+    #[test]
+    fn slides_along_floor() {
+        let (world, _) = world_with_floor();
+        let result = move_and_slide(
+            &world,
+            &block(),
+            Isometry2::translation(0.0, 20.0),
+            Vector2::new(50.0, -100.0),
+            0.1,
+            &CollisionGroups::new(),
+        );
+        assert!((result.isometry.translation.y - 16.0).abs() < 0.01);
+        assert!(result.isometry.translation.x > 2.0);
+        assert!((result.velocity.x - 50.0).abs() < 0.001);
+        assert!(result.velocity.y.abs() < 0.001);
+    }
+
+    #[test]
+    fn passes_through_sensors() {
+        let (mut world, _) = world_with_floor();
+        let sensor = World::new().create_entity().build();
+        world.add(
+            Isometry2::translation(20.0, 50.0),
+            CollisionShape::cuboid(Vector2::new(4.0, 4.0)),
+            CollisionGroups::new(),
+            QueryType::Proximity,
+            sensor,
+        );
+        world.update();
+        let result = move_and_slide(
+            &world,
+            &block(),
+            Isometry2::translation(0.0, 50.0),
+            Vector2::new(100.0, 0.0),
+            0.5,
+            &CollisionGroups::new(),
+        );
+        assert_eq!(result.isometry.translation.vector, Vector2::new(50.0, 50.0));
+        assert_eq!(result.velocity, Vector2::new(100.0, 0.0));
+        assert!(result.hits.is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use super::geometry::CollisionGroups;
+
+/// Named collision groups. Which layers interact with which is configured in
+/// `collision_layers.ron`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionLayer {
+    Player,
+    Terrain,
+    Enemy,
+    Pickup,
+    Sensor,
+    Projectile,
+}
+
+impl CollisionLayer {
+    pub const ALL: [CollisionLayer; 6] = [
+        CollisionLayer::Player,
+        CollisionLayer::Terrain,
+        CollisionLayer::Enemy,
+        CollisionLayer::Pickup,
+        CollisionLayer::Sensor,
+        CollisionLayer::Projectile,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        use CollisionLayer::*;
+        Some(match name {
+            "player" => Player,
+            "terrain" => Terrain,
+            "enemy" => Enemy,
+            "pickup" => Pickup,
+            "sensor" => Sensor,
+            "projectile" => Projectile,
+            _ => return None,
+        })
+    }
+
+    pub fn group_id(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CollisionLayersConfig {
+    /// For each layer, the layers it interacts with. This is symmetric:
+    /// listing `b` under `a` is enough for `a` and `b` to interact.
+    pub interactions: HashMap<CollisionLayer, Vec<CollisionLayer>>,
+}
+
+impl CollisionLayersConfig {
+    pub fn interacts(&self, a: CollisionLayer, b: CollisionLayer) -> bool {
+        let listed = |x, y| {
+            self.interactions
+                .get(&x)
+                .map_or(false, |layers| layers.contains(&y))
+        };
+        listed(a, b) || listed(b, a)
+    }
+
+    pub fn groups(&self, layer: CollisionLayer) -> CollisionGroups {
+        let whitelist = CollisionLayer::ALL
+            .iter()
+            .copied()
+            .filter(|&other| other != layer && self.interacts(layer, other))
+            .map(CollisionLayer::group_id)
+            .collect::<Vec<_>>();
+        let mut groups = CollisionGroups::new()
+            .with_membership(&[layer.group_id()])
+            .with_whitelist(&whitelist);
+        if !self.interacts(layer, layer) {
+            groups.disable_self_interaction();
+        }
+        groups
+    }
+}
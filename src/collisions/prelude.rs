@@ -5,10 +5,13 @@ use nalgebra::{
 };
 
 pub trait TransformExt {
+    /// The position in the xy plane, with the rotation projected onto it.
     fn to_2d_isometry(&self) -> Isometry2<f32>;
+    /// The x and y scale. Negative components are mirrored axes.
+    fn scale_2d(&self) -> Vector2<f32>;
 }
 impl TransformExt for Transform {
-    fn to_2d_isometry(&self) -> ncollide2d::math::Isometry<f32> {
+    fn to_2d_isometry(&self) -> Isometry2<f32> {
         let isometry = self.isometry();
 
         let translation = {
@@ -18,23 +21,29 @@ impl TransformExt for Transform {
 
             Translation2::new(x, y)
         };
-        let rotation = match isometry.rotation.axis_angle() {
-            Some((axis, angle)) => {
-                assert!(axis.into_inner() == Vector3::z());
-                UnitComplex::new(angle)
-            }
-            None => UnitComplex::identity(),
-        };
+        // axis_angle may report a rotation about the z axis as one about -z,
+        // and rounding can tip it slightly off axis, so rather than reading
+        // the angle directly we look at where the x axis ends up.
+        let x_axis = isometry.rotation * Vector3::x();
+        let rotation = UnitComplex::new(x_axis.y.atan2(x_axis.x));
 
         Isometry2::from_parts(translation, rotation)
     }
+
+    fn scale_2d(&self) -> Vector2<f32> {
+        self.scale().xy()
+    }
 }
 pub trait IsometryExt {
+    /// A `Transform` at z = 0 with unit scale. Prefer updating an existing
+    /// `Transform` to keep its depth and scale.
     fn to_transform(&self) -> Transform;
+    /// Moves by `distance` along the world-space `direction`, keeping the
+    /// rotation unchanged.
     fn prepend_movement(&self, direction: Unit<Vector2<f32>>, distance: f32) -> Self;
 }
 
-impl IsometryExt for ncollide2d::math::Isometry<f32> {
+impl IsometryExt for Isometry2<f32> {
     fn to_transform(&self) -> Transform {
         let translation = {
             let x = self.translation.vector.x;
@@ -55,3 +64,36 @@ impl IsometryExt for ncollide2d::math::Isometry<f32> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use amethyst::core::Transform;
+    use nalgebra::{UnitQuaternion, Vector2, Vector3};
+
+    use super::TransformExt;
+
+    #[test]
+    fn rotation_about_negative_z() {
+        let mut transform = Transform::default();
+        transform.set_rotation(UnitQuaternion::from_axis_angle(&-Vector3::z_axis(), FRAC_PI_2));
+        let angle = transform.to_2d_isometry().rotation.angle();
+        assert!((angle + FRAC_PI_2).abs() < 0.0001);
+    }
+
+    #[test]
+    fn rotation_about_z() {
+        let mut transform = Transform::default();
+        transform.set_rotation_2d(0.5);
+        let angle = transform.to_2d_isometry().rotation.angle();
+        assert!((angle - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn scale() {
+        let mut transform = Transform::default();
+        transform.set_scale(Vector3::new(-2.0, 3.0, 1.0));
+        assert_eq!(transform.scale_2d(), Vector2::new(-2.0, 3.0));
+    }
+}
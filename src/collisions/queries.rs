@@ -0,0 +1,164 @@
+use amethyst::{ecs::prelude::*, shred::ResourceId};
+use nalgebra::{Isometry2, Point2, Unit, Vector2};
+
+use super::{
+    backend::{PhysicsBackend, PhysicsWorld},
+    geometry::{CollisionGroups, CollisionShape, SweepHit},
+};
+
+/// Read-only spatial queries against the collision world, in terms of
+/// entities rather than backend handles.
+///
+/// Positions are world-space; use `TransformExt::to_2d_isometry` to get an
+/// isometry from a `Transform`.
+#[derive(SystemData)]
+pub struct PhysicsQueries<'a> {
+    physics_world: Read<'a, PhysicsWorld>,
+}
+
+impl<'a> PhysicsQueries<'a> {
+    /// Casts a ray, returning the nearest entity hit along with the hit
+    /// point and the surface normal there.
+    pub fn raycast(
+        &self,
+        origin: Point2<f32>,
+        direction: Unit<Vector2<f32>>,
+        max_distance: f32,
+        groups: &CollisionGroups,
+    ) -> Option<(Entity, Point2<f32>, Unit<Vector2<f32>>)> {
+        let world = &*self.physics_world;
+        world
+            .interferences_with_ray(&origin, &direction, max_distance, groups)
+            .into_iter()
+            .min_by(|(_, hit1), (_, hit2)| hit1.toi.partial_cmp(&hit2.toi).unwrap())
+            .and_then(|(handle, hit)| {
+                Some((
+                    world.object(handle)?.entity,
+                    origin + direction.into_inner() * hit.toi,
+                    Unit::new_normalize(hit.normal),
+                ))
+            })
+    }
+
+    /// Finds every entity whose shape intersects `shape` placed at
+    /// `isometry`.
+    pub fn overlap<'b>(
+        &'b self,
+        shape: &CollisionShape,
+        isometry: &Isometry2<f32>,
+        groups: &CollisionGroups,
+    ) -> impl Iterator<Item = Entity> + 'b {
+        let world = &*self.physics_world;
+        world
+            .intersections_with_shape(shape, isometry, groups)
+            .into_iter()
+            .filter_map(move |handle| Some(world.object(handle)?.entity))
+    }
+
+    /// Sweeps `shape` from `isometry` along `direction`, returning the first
+    /// entity it would hit within `max_distance`.
+    pub fn shape_cast(
+        &self,
+        shape: &CollisionShape,
+        isometry: &Isometry2<f32>,
+        direction: Unit<Vector2<f32>>,
+        max_distance: f32,
+        groups: &CollisionGroups,
+    ) -> Option<(Entity, SweepHit)> {
+        let world = &*self.physics_world;
+        world
+            .sweep_test(shape, isometry, &direction, max_distance, groups)
+            .into_iter()
+            .min_by(|(_, toi1), (_, toi2)| toi1.toi.partial_cmp(&toi2.toi).unwrap())
+            .and_then(|(handle, toi)| Some((world.object(handle)?.entity, toi)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::ecs::prelude::*;
+    use nalgebra::{Isometry2, Point2, Vector2};
+
+    use super::PhysicsQueries;
+    use crate::collisions::{
+        backend::{PhysicsBackend, PhysicsWorld},
+        geometry::{CollisionGroups, CollisionShape, QueryType},
+    };
+
+    const WALL_GROUP: usize = 1;
+    const CRATE_GROUP: usize = 2;
+
+    /// A wall to the right of the origin and a crate above it, each in its
+    /// own collision group.
+    fn world() -> (World, Entity, Entity) {
+        let mut world = World::new();
+        let wall = world.create_entity().build();
+        let crate_ = world.create_entity().build();
+        let mut physics_world = PhysicsWorld::default();
+        let mut add = |x, y, group, entity| {
+            physics_world.add(
+                Isometry2::translation(x, y),
+                CollisionShape::cuboid(Vector2::new(8.0, 8.0)),
+                CollisionGroups::new().with_membership(&[group]),
+                QueryType::Contacts { prediction: 0.0 },
+                entity,
+            );
+        };
+        add(50.0, 0.0, WALL_GROUP, wall);
+        add(0.0, 50.0, CRATE_GROUP, crate_);
+        physics_world.update();
+        world.insert(physics_world);
+        (world, wall, crate_)
+    }
+
+    #[test]
+    fn raycast_hits_nearest() {
+        let (world, wall, _) = world();
+        let queries = world.system_data::<PhysicsQueries>();
+        let (entity, point, normal) = queries
+            .raycast(Point2::origin(), Vector2::x_axis(), 100.0, &CollisionGroups::new())
+            .unwrap();
+        assert_eq!(entity, wall);
+        assert!((point.x - 42.0).abs() < 0.01);
+        assert!(normal.x < -0.99);
+    }
+
+    #[test]
+    fn raycast_misses() {
+        let (world, _, _) = world();
+        let queries = world.system_data::<PhysicsQueries>();
+        let groups = CollisionGroups::new();
+        assert!(queries.raycast(Point2::origin(), Vector2::x_axis(), 30.0, &groups).is_none());
+        assert!(queries.raycast(Point2::origin(), -Vector2::x_axis(), 100.0, &groups).is_none());
+    }
+
+    #[test]
+    fn overlap_filters_groups() {
+        let (world, wall, _) = world();
+        let queries = world.system_data::<PhysicsQueries>();
+        let shape = CollisionShape::cuboid(Vector2::new(4.0, 4.0));
+        let isometry = Isometry2::translation(45.0, 0.0);
+        let walls = CollisionGroups::new().with_whitelist(&[WALL_GROUP]);
+        let crates = CollisionGroups::new().with_whitelist(&[CRATE_GROUP]);
+        assert_eq!(queries.overlap(&shape, &isometry, &walls).collect::<Vec<_>>(), vec![wall]);
+        assert_eq!(queries.overlap(&shape, &isometry, &crates).count(), 0);
+    }
+
+    #[test]
+    fn shape_cast_finds_time_of_impact() {
+        let (world, _, crate_) = world();
+        let queries = world.system_data::<PhysicsQueries>();
+        let shape = CollisionShape::cuboid(Vector2::new(8.0, 8.0));
+        let (entity, toi) = queries
+            .shape_cast(
+                &shape,
+                &Isometry2::identity(),
+                Vector2::y_axis(),
+                100.0,
+                &CollisionGroups::new(),
+            )
+            .unwrap();
+        assert_eq!(entity, crate_);
+        assert!((toi.toi - 34.0).abs() < 0.01);
+    }
+}
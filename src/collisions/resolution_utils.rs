@@ -1,53 +1,33 @@
+use amethyst::ecs::Entity;
 use nalgebra::{Isometry2, Unit, Vector2};
-use ncollide2d::{
-    pipeline::{object::CollisionObjectSlabHandle, CollisionObject, CollisionWorld},
-    query::Contact,
-    shape::Shape,
-};
 
-use super::prelude::IsometryExt;
+use super::{
+    backend::{ObjectRef, PhysicsBackend},
+    geometry::{CollisionShape, Contact},
+    prelude::IsometryExt,
+};
 
 const DIRECTION_TEST_DELTA: f32 = 0.001;
 
-pub fn contact_in_direction<T>(
-    obj1: &CollisionObject<f32, T>,
-    obj2: &CollisionObject<f32, T>,
+pub fn contact_in_direction<B: PhysicsBackend>(
+    world: &B,
+    obj1: &ObjectRef,
+    obj2: &ObjectRef,
     direction: Unit<Vector2<f32>>,
-) -> Option<Contact<f32>> {
-    let isometry = obj1.position();
-    let in_depth = isometry.prepend_movement(direction, DIRECTION_TEST_DELTA);
-    let contact1 = ncollide2d::query::contact(
-        obj1.position(),
-        &**obj1.shape(),
-        obj2.position(),
-        &**obj2.shape(),
-        0.0,
-    )?;
-    let contact2 = ncollide2d::query::contact(
-        &in_depth,
-        &**obj1.shape(),
-        obj2.position(),
-        &**obj2.shape(),
-        0.0,
-    )?;
-    if contact2.depth > contact1.depth {
-        Some(contact2)
-    } else {
-        None
-    }
+) -> Option<Contact> {
+    contact_in_direction_with_shape(world, obj1.position, obj1.shape, obj2, direction)
 }
 
-pub fn contact_in_direction_with_shape<T>(
+pub fn contact_in_direction_with_shape<B: PhysicsBackend>(
+    world: &B,
     isometry: &Isometry2<f32>,
-    shape: &dyn Shape<f32>,
-    obj2: &CollisionObject<f32, T>,
+    shape: &CollisionShape,
+    obj2: &ObjectRef,
     direction: Unit<Vector2<f32>>,
-) -> Option<Contact<f32>> {
+) -> Option<Contact> {
     let in_depth = isometry.prepend_movement(direction, DIRECTION_TEST_DELTA);
-    let contact1 =
-        ncollide2d::query::contact(isometry, shape, obj2.position(), &**obj2.shape(), 0.0)?;
-    let contact2 =
-        ncollide2d::query::contact(&in_depth, shape, obj2.position(), &**obj2.shape(), 0.0)?;
+    let contact1 = world.contact(isometry, shape, obj2.position, obj2.shape, 0.0)?;
+    let contact2 = world.contact(&in_depth, shape, obj2.position, obj2.shape, 0.0)?;
     if contact2.depth > contact1.depth {
         Some(contact2)
     } else {
@@ -55,20 +35,20 @@ pub fn contact_in_direction_with_shape<T>(
     }
 }
 
-pub fn on_floor<T>(
-    ncollide_world: &CollisionWorld<f32, T>,
-    handle: CollisionObjectSlabHandle,
-) -> bool {
-    ncollide_world
-        .contacts_with(handle, true)
-        .into_iter()
-        .flat_map(|v| v)
-        .any(|(handle1, handle2, _algo, manifold)| {
-            contact_in_direction(
-                ncollide_world.objects.get(handle1).unwrap(),
-                ncollide_world.objects.get(handle2).unwrap(),
-                -Vector2::y_axis(),
-            )
-            .is_some()
-        })
+/// Iterates over every entity the given object is resting on, along with the
+/// contact pushing it `down` into that entity's object.
+///
+/// Either object may be rotated; only the direction of `down` decides what
+/// counts as a floor.
+pub fn floor_contacts<'a, B: PhysicsBackend>(
+    world: &'a B,
+    handle: B::Handle,
+    down: Unit<Vector2<f32>>,
+) -> impl Iterator<Item = (Entity, Contact)> + 'a {
+    world.contacts_with(handle).into_iter().filter_map(move |other| {
+        let this_obj = world.object(handle)?;
+        let other_obj = world.object(other)?;
+        contact_in_direction(world, &this_obj, &other_obj, down)
+            .map(|contact| (other_obj.entity, contact))
+    })
 }
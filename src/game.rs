@@ -4,12 +4,16 @@ use amethyst::{
     ecs::prelude::{Component, DenseVecStorage, Entity},
     prelude::*,
     renderer::{Camera, ImageFormat, SpriteRender, SpriteSheet, SpriteSheetFormat, Texture},
-    ui::{Anchor, TtfFormat, UiText, UiTransform},
+    ui::{Anchor, FontHandle, TtfFormat, UiText, UiTransform},
 };
 use nalgebra::{UnitQuaternion, Vector3};
 
 use crate::{
-    systems::{CameraTarget, CameraVelocity, TrackingCamera},
+    collisions::layers::CollisionLayer,
+    systems::{
+        CameraShaker, CameraTarget, CameraVelocity, CollectiblesText, PixelPerfectCamera,
+        TrackingCamera,
+    },
     world,
 };
 
@@ -36,12 +40,14 @@ fn initialize_camera(world: &mut World) {
         .with(transform)
         .with(CameraVelocity::default())
         .with(TrackingCamera::default())
+        .with(CameraShaker::default())
+        .with(PixelPerfectCamera::default())
         .build();
 }
 
 fn initialize_box(world: &mut World, sprite_render: SpriteRender) {
     (0..20).for_each(|i| {
-        world::create_platform(world)
+        world::create_platform(world, CollisionLayer::Terrain)
             .with(
                 Transform::default()
                     .append_translation([i as f32 * 16.0, 0.0, 0.0].into())
@@ -51,7 +57,7 @@ fn initialize_box(world: &mut World, sprite_render: SpriteRender) {
             .build();
     });
     (0..20).for_each(|i| {
-        world::create_platform(world)
+        world::create_platform(world, CollisionLayer::Terrain)
             .with(
                 Transform::default()
                     .append_translation([i as f32 * 16.0, 16.0 * 19.0, 0.0].into())
@@ -61,7 +67,7 @@ fn initialize_box(world: &mut World, sprite_render: SpriteRender) {
             .build();
     });
     (0..20).for_each(|i| {
-        world::create_platform(world)
+        world::create_platform(world, CollisionLayer::Terrain)
             .with(
                 Transform::default()
                     .append_translation([0.0, i as f32 * 16.0, 0.0].into())
@@ -71,7 +77,7 @@ fn initialize_box(world: &mut World, sprite_render: SpriteRender) {
             .build();
     });
     (0..20).for_each(|i| {
-        world::create_platform(world)
+        world::create_platform(world, CollisionLayer::Terrain)
             .with(
                 Transform::default()
                     .append_translation([16.0 * 19.0, i as f32 * 16.0, 0.0].into())
@@ -83,7 +89,7 @@ fn initialize_box(world: &mut World, sprite_render: SpriteRender) {
 }
 
 fn initialize_single(world: &mut World, sprite_render: SpriteRender) {
-    world::create_platform(world)
+    world::create_platform(world, CollisionLayer::Terrain)
         .with(
             Transform::default()
                 .append_translation([40.0, 20.0, 0.0].into())
@@ -95,7 +101,7 @@ fn initialize_single(world: &mut World, sprite_render: SpriteRender) {
 
 fn initialize_stairs(world: &mut World, sprite_render: SpriteRender) {
     (0..20).for_each(|i| {
-        world::create_platform(world)
+        world::create_platform(world, CollisionLayer::Terrain)
             .with(
                 Transform::default()
                     .append_translation([i as f32 * 16.0, i as f32 * 16.0, 0.0].into())
@@ -135,6 +141,7 @@ fn initialize_player(world: &mut World, sprite_sheet: Handle<SpriteSheet>) {
         .with(CameraTarget {
             offset: Vector3::new(0.0, 0.0, 1.0),
             target_rotation: UnitQuaternion::identity(),
+            weight: 1.0,
         })
         .with(sprite_render.clone())
         .build();
@@ -160,13 +167,43 @@ fn initialize_ball(world: &mut World, sprite_sheet_handle: Handle<SpriteSheet>)
         .build();
 }
 
-fn initialize_scoreboard(world: &mut World) {
-    let font = world.read_resource::<Loader>().load(
+fn load_font(world: &mut World) -> FontHandle {
+    world.read_resource::<Loader>().load(
         "font/square.ttf",
         TtfFormat,
         (),
         &world.read_resource(),
+    )
+}
+
+fn initialize_collectibles_hud(world: &mut World) {
+    let font = load_font(world);
+    let transform = UiTransform::new(
+        "collectibles".to_string(),
+        Anchor::TopLeft,
+        Anchor::TopLeft,
+        20.0,
+        -20.0,
+        1.0,
+        200.0,
+        30.0,
     );
+    let text = world
+        .create_entity()
+        .with(transform)
+        .with(UiText::new(
+            font,
+            "0/0".to_string(),
+            [1.0, 1.0, 1.0, 1.0],
+            25.0,
+        ))
+        .build();
+
+    world.insert(CollectiblesText { text });
+}
+
+fn initialize_scoreboard(world: &mut World) {
+    let font = load_font(world);
 
     let p1_transform = UiTransform::new(
         "P1".to_string(),
@@ -250,6 +287,7 @@ impl SimpleState for Game {
         world.register::<world::Platform>();
 
         initialize_camera(world);
+        initialize_collectibles_hud(world);
         // initialize_scoreboard(world);
         // initialize_audio(world);
         // initialize_platforms(world, sprite_sheet.clone());
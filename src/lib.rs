@@ -4,7 +4,7 @@ use amethyst::{
     input::{InputBundle, StringBindings},
     prelude::*,
     renderer::{
-        plugins::{RenderFlat2D, RenderToWindow},
+        plugins::{RenderDebugLines, RenderFlat2D, RenderToWindow},
         types::DefaultBackend,
         RenderingBundle,
     },
@@ -19,7 +19,11 @@ mod systems;
 mod tiles;
 mod world;
 
-use crate::{game::Game, world::ConstantsConfig};
+use crate::{
+    collisions::layers::CollisionLayersConfig,
+    game::Game,
+    world::{AccessibilityConfig, ConstantsConfig},
+};
 use world::MapsConfig;
 
 pub fn run() -> amethyst::Result<()> {
@@ -29,6 +33,8 @@ pub fn run() -> amethyst::Result<()> {
     let bindings_path = config.join("bindings.ron");
     let constants_path = config.join("constants.ron");
     let maps_path = config.join("maps.ron");
+    let collision_layers_path = config.join("collision_layers.ron");
+    let accessibility_path = config.join("accessibility.ron");
 
     let input_bundle =
         InputBundle::<StringBindings>::new().with_bindings_from_file(bindings_path)?;
@@ -41,6 +47,7 @@ pub fn run() -> amethyst::Result<()> {
                         .with_clear([0.0, 0.0, 0.0, 1.0]),
                 )
                 .with_plugin(RenderFlat2D::default())
+                .with_plugin(RenderDebugLines::default())
                 .with_plugin(RenderUi::default()),
         )?
         .with_bundle(TransformBundle::new())?
@@ -61,46 +68,97 @@ pub fn run() -> amethyst::Result<()> {
         //     "dj_system",
         //     &[],
         // )
+        .with(systems::PhysicsSyncSystem::default(), "physics_sync", &[])
         .with(
-            systems::Ncollide2dSyncPresencesSystem::default(),
-            "ncollide2d_sync_presence",
-            &[],
+            systems::PhysicsUpdateWorldSystem::default(),
+            "physics_update_world",
+            &["physics_sync"],
         )
         .with(
-            systems::Ncollide2dSyncTransformsSystem::default(),
-            "ncollide2d_sync_transform",
-            &[],
+            systems::CollisionEventsSystem,
+            "collision_events",
+            &["physics_update_world"],
         )
         .with(
-            systems::Ncollide2dUpdateWorldSystem::default(),
-            "ncollide2d_update_world",
-            &["ncollide2d_sync_presence", "ncollide2d_sync_transform"],
+            systems::GroundProbeSystem,
+            "ground_probe",
+            &["physics_update_world"],
         )
         .with(
             systems::MovePlayerSystem,
             "move_player",
-            &["ncollide2d_update_world"],
+            &["physics_update_world", "ground_probe"],
         )
         .with(
             systems::GravitySystem,
             "gravity",
-            &["ncollide2d_update_world", "move_player"],
+            &["physics_update_world", "ground_probe", "move_player"],
+        )
+        .with(
+            systems::SpringSystem,
+            "springs",
+            &["physics_update_world", "ground_probe", "move_player", "gravity"],
         )
         .with(
             systems::ApplyVelocity,
             "apply_velocity",
-            &["ncollide2d_update_world", "move_player", "gravity"],
+            &["physics_update_world", "move_player", "gravity", "springs"],
+        )
+        .with(
+            systems::CrumblingPlatformSystem,
+            "crumbling_platforms",
+            &["apply_velocity"],
+        )
+        .with(systems::TimedPlatformSystem, "timed_platforms", &[])
+        .with(
+            systems::CollectPickupsSystem::default(),
+            "collect_pickups",
+            &["collision_events"],
+        )
+        .with(
+            systems::CollectiblesHudSystem,
+            "collectibles_hud",
+            &["collect_pickups"],
+        )
+        .with(
+            systems::CameraRoomsSystem,
+            "camera_rooms",
+            &["apply_velocity"],
         )
         .with(
             systems::CameraTrackTargetSystem,
             "track_camera",
-            &["apply_velocity"],
+            &["apply_velocity", "camera_rooms"],
+        )
+        .with(systems::LandingShakeSystem, "landing_shake", &["ground_probe"])
+        .with(
+            systems::CameraShakeSystem::default(),
+            "camera_shake",
+            &["track_camera", "landing_shake"],
+        )
+        .with(
+            systems::PixelPerfectCameraSystem,
+            "pixel_perfect_camera",
+            &["camera_shake"],
+        )
+        .with(
+            systems::ParallaxSystem,
+            "parallax",
+            &["pixel_perfect_camera"],
+        )
+        .with(systems::SpriteAnimationSystem, "sprite_animation", &[])
+        .with(
+            systems::CollisionDebugSystem::default(),
+            "collision_debug",
+            &["apply_velocity", "ground_probe"],
         );
 
     let assets_dir = app_root.join("assets");
     let mut game = Application::build(assets_dir, Game::default())?
         .with_resource(ConstantsConfig::load(constants_path)?)
         .with_resource(MapsConfig::load(maps_path)?)
+        .with_resource(CollisionLayersConfig::load(collision_layers_path)?)
+        .with_resource(AccessibilityConfig::load(accessibility_path)?)
         .build(game_data)?;
     game.run();
 
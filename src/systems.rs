@@ -1,14 +1,26 @@
 mod apply_velocity;
-mod bounce;
 mod camera_follow;
+mod camera_rooms;
+mod camera_shake;
+mod collectibles;
+mod collision_debug;
+mod collision_events;
 mod gravity;
+mod ground_probe;
 mod move_balls;
 mod move_player;
-mod ncollide2d_sync;
 mod paddle;
+mod parallax;
+mod physics_sync;
+mod pixel_perfect;
+mod platforms;
+mod spring;
+mod sprite_animation;
 mod winner;
 
 pub use self::{
-    apply_velocity::*, bounce::*, camera_follow::*, gravity::*, move_balls::*, move_player::*,
-    ncollide2d_sync::*, paddle::*, winner::*,
+    apply_velocity::*, camera_follow::*, camera_rooms::*, camera_shake::*, collectibles::*,
+    collision_debug::*, collision_events::*, gravity::*, ground_probe::*, move_balls::*,
+    move_player::*, paddle::*, parallax::*, physics_sync::*, pixel_perfect::*, platforms::*,
+    spring::*, sprite_animation::*, winner::*,
 };
@@ -1,25 +1,145 @@
+use std::cmp::Ordering;
+
 use amethyst::{
     core::{timing::Time, Transform},
     derive::SystemDesc,
     ecs::prelude::*,
+    renderer::Camera,
 };
-use nalgebra::{UnitQuaternion, Vector3};
+use nalgebra::{UnitQuaternion, Vector2, Vector3};
 
-use crate::world::*;
+use crate::{
+    collisions::components::GroundState,
+    game::{ARENA_HEIGHT, ARENA_WIDTH},
+    systems::{CameraRooms, CameraShaker, PixelPerfectCamera},
+    world::*,
+};
 
-#[derive(Default)]
 pub struct TrackingCamera {
     started_tracking: bool,
+    /// The point the dead zone is centered on.
+    focus: Vector2<f32>,
+    /// Current horizontal look-ahead offset.
+    look_ahead: f32,
+    /// -1.0 or 1.0 once the target has moved horizontally.
+    facing: f32,
+    /// The orthographic left, right, bottom and top the camera started
+    /// with, which `zoom` multiplies.
+    base_view: Option<[f32; 4]>,
+    zoom: f32,
+}
+
+impl Default for TrackingCamera {
+    fn default() -> Self {
+        TrackingCamera {
+            started_tracking: false,
+            focus: Vector2::zeros(),
+            look_ahead: 0.0,
+            facing: 0.0,
+            base_view: None,
+            zoom: 1.0,
+        }
+    }
 }
 
 impl Component for TrackingCamera {
     type Storage = DenseVecStorage<Self>;
 }
 
+/// Horizontal speed below which the look-ahead keeps its direction.
+const LOOK_AHEAD_MIN_SPEED: f32 = 1.0;
+
+impl TrackingCamera {
+    /// Moves the focus just enough to keep `target` inside the dead zone,
+    /// eases the look-ahead towards the direction the target is moving in,
+    /// and returns where the camera should be.
+    fn follow(
+        &mut self,
+        target: Vector2<f32>,
+        target_velocity: Vector2<f32>,
+        grounded: bool,
+        constants: &CameraFollowConstants,
+        delta_t: f32,
+    ) -> Vector2<f32> {
+        let half_dead_zone =
+            Vector2::new(constants.dead_zone_width, constants.dead_zone_height) / 2.0;
+        self.focus.x = self
+            .focus
+            .x
+            .max(target.x - half_dead_zone.x)
+            .min(target.x + half_dead_zone.x);
+        if constants.platform_snap && grounded {
+            self.focus.y = target.y;
+        } else {
+            // even when snapping to platforms, follow long falls and jumps.
+            self.focus.y = self
+                .focus
+                .y
+                .max(target.y - half_dead_zone.y)
+                .min(target.y + half_dead_zone.y);
+        }
+
+        if target_velocity.x.abs() > LOOK_AHEAD_MIN_SPEED {
+            self.facing = target_velocity.x.signum();
+        }
+        let wanted_look_ahead = self.facing * constants.look_ahead_distance;
+        let progress = (delta_t / constants.look_ahead_secs).finite_or(1.0).min(1.0);
+        self.look_ahead += (wanted_look_ahead - self.look_ahead) * progress;
+
+        self.focus + Vector2::new(self.look_ahead, 0.0)
+    }
+
+    /// Current zoom, where 1.0 shows the base view.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Replaces the view `zoom` multiplies, such as when the window is
+    /// resized.
+    pub fn set_base_view(&mut self, half_view: Vector2<f32>) {
+        self.base_view = Some([-half_view.x, half_view.x, -half_view.y, half_view.y]);
+    }
+
+    /// Eases the zoom towards the smallest one, within the configured
+    /// limits, which shows everything within `extent` of the center.
+    fn zoom_to_fit(
+        &mut self,
+        camera: &mut Camera,
+        extent: Vector2<f32>,
+        constants: &CameraFollowConstants,
+        delta_t: f32,
+    ) {
+        let ortho = match camera.projection_mut().as_orthographic_mut() {
+            Some(v) => v,
+            None => return,
+        };
+        let [left, right, bottom, top] = *self.base_view.get_or_insert_with(|| {
+            [ortho.left(), ortho.right(), ortho.bottom(), ortho.top()]
+        });
+        let base_half_view = Vector2::new((right - left).abs(), (top - bottom).abs()) / 2.0;
+        let needed = (extent + Vector2::repeat(constants.framing_margin))
+            .component_div(&base_half_view)
+            .max();
+        let wanted = needed.max(constants.min_zoom).min(constants.max_zoom);
+        if self.started_tracking {
+            let progress = (delta_t / constants.time_to_target).finite_or(1.0).min(1.0);
+            self.zoom += (wanted - self.zoom) * progress;
+        } else {
+            self.zoom = wanted;
+        }
+        ortho.set_left_and_right(left * self.zoom, right * self.zoom);
+        ortho.set_bottom_and_top(bottom * self.zoom, top * self.zoom);
+    }
+}
+
+/// Something cameras keep in view. With several targets, cameras center on
+/// their weighted average position and zoom out to show all of them.
 #[derive(Clone)]
 pub struct CameraTarget {
     pub offset: Vector3<f32>,
     pub target_rotation: UnitQuaternion<f32>,
+    /// How strongly this target pulls the center of the view towards it.
+    pub weight: f32,
 }
 
 impl Component for CameraTarget {
@@ -42,10 +162,132 @@ impl Default for CameraVelocity {
     }
 }
 
+/// The area cameras are kept inside, in world coordinates. Set when a map is
+/// loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraBounds {
+    pub min: Vector2<f32>,
+    pub max: Vector2<f32>,
+}
+
+impl CameraBounds {
+    pub fn contains(&self, point: Vector2<f32>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    /// Bounds `t` of the way from `self` to `other`.
+    pub fn lerp(&self, other: &CameraBounds, t: f32) -> CameraBounds {
+        CameraBounds {
+            min: self.min.lerp(&other.min, t),
+            max: self.max.lerp(&other.max, t),
+        }
+    }
+
+    /// Moves `center` so that a view with the given half extents stays
+    /// inside the bounds. On an axis where the bounds are smaller than the
+    /// view, the view is centered on the bounds instead.
+    pub fn clamp(&self, center: Vector2<f32>, half_view: Vector2<f32>) -> Vector2<f32> {
+        let clamp_axis = |center: f32, min: f32, max: f32, half_view: f32| {
+            if max - min <= half_view * 2.0 {
+                (min + max) / 2.0
+            } else {
+                center.max(min + half_view).min(max - half_view)
+            }
+        };
+        Vector2::new(
+            clamp_axis(center.x, self.min.x, self.max.x, half_view.x),
+            clamp_axis(center.y, self.min.y, self.max.y, half_view.y),
+        )
+    }
+}
+
+/// Half the width and height of the area a camera shows.
+fn half_view(camera: &Camera) -> Vector2<f32> {
+    match camera.projection().as_orthographic() {
+        Some(ortho) => Vector2::new(
+            (ortho.right() - ortho.left()).abs() / 2.0,
+            (ortho.top() - ortho.bottom()).abs() / 2.0,
+        ),
+        None => Vector2::new(ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0),
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct CameraFollowConstants {
     time_to_target: f32,
     smoothing_factor: f32,
+    /// Size of the area around the focus point the target can move in
+    /// without moving the camera.
+    dead_zone_width: f32,
+    dead_zone_height: f32,
+    /// How far ahead of the target, horizontally, the camera looks.
+    look_ahead_distance: f32,
+    /// Roughly how long the look-ahead takes to swap sides.
+    look_ahead_secs: f32,
+    /// Only re-center vertically when the target lands, rather than
+    /// following every jump.
+    platform_snap: bool,
+    /// Limits on the zoom used to fit several targets in view. 1.0 is the
+    /// camera's original view, and larger values show more.
+    min_zoom: f32,
+    max_zoom: f32,
+    /// Space kept between the outermost targets and the edge of the view.
+    framing_margin: f32,
+}
+
+/// A `CameraTarget` and where it is this frame.
+struct TargetState {
+    translation: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    weight: f32,
+    velocity: Vector2<f32>,
+    /// `None` if the target has no `GroundState`.
+    grounded: Option<bool>,
+}
+
+/// Where a group of targets should be framed.
+struct Framing {
+    translation: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    velocity: Vector2<f32>,
+    /// Whether every target with a `GroundState` is grounded. False if none
+    /// of them have one.
+    grounded: bool,
+    /// Distance from `translation` to the furthest target on each axis.
+    extent: Vector2<f32>,
+}
+
+fn frame_targets(targets: &[TargetState]) -> Option<Framing> {
+    let heaviest = targets
+        .iter()
+        .max_by(|a, b| a.weight.partial_cmp(&b.weight).unwrap_or(Ordering::Equal))?;
+    let total_weight: f32 = targets.iter().map(|t| t.weight).sum();
+    // fall back to an unweighted average if nothing has any weight.
+    let weight = |target: &TargetState| {
+        if total_weight > 0.0 {
+            target.weight / total_weight
+        } else {
+            1.0 / targets.len() as f32
+        }
+    };
+    let translation = targets
+        .iter()
+        .fold(Vector3::zeros(), |sum, t| sum + t.translation * weight(t));
+    let velocity = targets
+        .iter()
+        .fold(Vector2::zeros(), |sum, t| sum + t.velocity * weight(t));
+    let extent = targets.iter().fold(Vector2::zeros(), |extent, t| {
+        let offset = (t.translation - translation).xy().abs();
+        extent.sup(&offset)
+    });
+    let ground_states: Vec<_> = targets.iter().filter_map(|t| t.grounded).collect();
+    Some(Framing {
+        translation,
+        rotation: heaviest.rotation,
+        velocity,
+        grounded: !ground_states.is_empty() && ground_states.iter().all(|&g| g),
+        extent,
+    })
 }
 
 #[derive(SystemDesc)]
@@ -57,8 +299,15 @@ impl<'s> System<'s> for CameraTrackTargetSystem {
         WriteStorage<'s, CameraVelocity>,
         WriteStorage<'s, TrackingCamera>,
         ReadStorage<'s, CameraTarget>,
+        ReadStorage<'s, Velocity>,
+        ReadStorage<'s, GroundState>,
+        WriteStorage<'s, Camera>,
+        ReadStorage<'s, CameraShaker>,
+        ReadStorage<'s, PixelPerfectCamera>,
         Read<'s, Time>,
         ReadExpect<'s, ConstantsConfig>,
+        Option<Read<'s, CameraBounds>>,
+        Option<Read<'s, CameraRooms>>,
     );
     fn run(
         &mut self,
@@ -67,45 +316,100 @@ impl<'s> System<'s> for CameraTrackTargetSystem {
             mut camera_velocities,
             mut tracking_cameras,
             camera_targets,
+            target_velocities,
+            ground_states,
+            mut cameras,
+            shakers,
+            pixel_cameras,
             time,
             constants,
+            bounds,
+            rooms,
         ): Self::SystemData,
     ) {
         let constants = &constants.camera_follow;
-        let mut target_data = None;
-        for (transform, tracking) in (&transforms, &camera_targets).join() {
-            assert!(target_data.is_none(), "duplicate camera tracking targets");
-            target_data.replace((transform.clone(), tracking.clone()));
-        }
-        let (target_translation, target_rotation) = {
-            let (mut transform, targeting_info) = match target_data {
-                Some(v) => v,
-                None => return,
-            };
-            transform.prepend_translation(targeting_info.offset);
-            (*transform.translation(), targeting_info.target_rotation)
+        let delta_t = time.delta_seconds();
+        let targets: Vec<_> = (
+            &transforms,
+            &camera_targets,
+            target_velocities.maybe(),
+            ground_states.maybe(),
+        )
+            .join()
+            .map(|(transform, target, velocity, ground)| {
+                let mut transform = transform.clone();
+                transform.prepend_translation(target.offset);
+                TargetState {
+                    translation: *transform.translation(),
+                    rotation: target.target_rotation,
+                    weight: target.weight,
+                    velocity: velocity.map_or_else(Vector2::zeros, |v| v.intended),
+                    grounded: ground.map(|ground| ground.grounded),
+                }
+            })
+            .collect();
+        let framing = match frame_targets(&targets) {
+            Some(v) => v,
+            None => return,
         };
+        let target_rotation = framing.rotation;
+        // levels with rooms keep cameras inside the current room instead.
+        let bounds = rooms
+            .as_ref()
+            .and_then(|rooms| rooms.bounds())
+            .or_else(|| bounds.as_deref());
+        let scripted = rooms.as_ref().map_or(false, |rooms| rooms.transitioning());
 
-        for (transform, velocity, tracking) in (
+        for (transform, velocity, tracking, camera, shaker, pixel_camera) in (
             &mut transforms,
             (&mut camera_velocities).maybe(),
             &mut tracking_cameras,
+            (&mut cameras).maybe(),
+            shakers.maybe(),
+            pixel_cameras.maybe(),
         )
             .join()
         {
+            // track from where the camera would be without the shake and
+            // pixel snapping, and put them back on afterwards.
+            let offset = shaker.map_or_else(Vector3::zeros, CameraShaker::offset)
+                + pixel_camera.map_or_else(Vector3::zeros, PixelPerfectCamera::snap);
+            if let Some(camera) = camera.as_mut() {
+                tracking.zoom_to_fit(camera, framing.extent, constants, delta_t);
+            }
+            let mut target_translation = framing.translation;
+            if tracking.started_tracking {
+                let followed = tracking.follow(
+                    target_translation.xy(),
+                    framing.velocity,
+                    framing.grounded,
+                    constants,
+                    delta_t,
+                );
+                target_translation.x = followed.x;
+                target_translation.y = followed.y;
+            } else {
+                tracking.focus = target_translation.xy();
+            }
+            // clamp the target rather than the camera itself, so that the
+            // camera moves smoothly when the bounds change.
+            if let (Some(bounds), Some(camera)) = (&bounds, &camera) {
+                let clamped = bounds.clamp(target_translation.xy(), half_view(camera));
+                target_translation.x = clamped.x;
+                target_translation.y = clamped.y;
+            }
             // Initialize camera at target.
             if !tracking.started_tracking {
-                transform.set_translation(target_translation);
+                transform.set_translation(target_translation + offset);
                 transform.set_rotation(target_rotation);
                 tracking.started_tracking = true;
                 continue;
             }
             match velocity {
-                Some(velocity) => {
+                Some(velocity) if !scripted => {
                     // Credit for the algorithm here to
                     // https://github.com/azriel91/autexousious/blob/0.19.0/crate/camera_play/src/system/camera_velocity_system.rs
-                    let here = *transform.translation();
-                    let delta_t = time.delta_seconds();
+                    let here = *transform.translation() - offset;
                     let distance = target_translation - here;
                     let target_velocity = distance / constants.time_to_target;
                     velocity.translation = velocity
@@ -113,13 +417,18 @@ impl<'s> System<'s> for CameraTrackTargetSystem {
                         .lerp(&target_velocity, constants.smoothing_factor * delta_t);
                     if velocity.translation.magnitude() * delta_t > distance.magnitude() {
                         velocity.translation = Vector3::zeros();
-                        transform.set_translation(target_translation);
+                        transform.set_translation(target_translation + offset);
                     } else {
-                        transform.set_translation(here + (delta_t * velocity.translation));
+                        let moved = here + (delta_t * velocity.translation);
+                        transform.set_translation(moved + offset);
                     }
                 }
-                None => {
-                    transform.set_translation(target_translation);
+                // room transitions are already eased, so follow them exactly.
+                velocity => {
+                    if let Some(velocity) = velocity {
+                        velocity.translation = Vector3::zeros();
+                    }
+                    transform.set_translation(target_translation + offset);
                     transform.set_rotation(target_rotation);
                 }
             };
@@ -139,3 +448,138 @@ impl FloatExt for f32 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{UnitQuaternion, Vector2, Vector3};
+
+    use super::{frame_targets, CameraBounds, CameraFollowConstants, TargetState, TrackingCamera};
+
+    fn bounds() -> CameraBounds {
+        CameraBounds {
+            min: Vector2::new(0.0, -200.0),
+            max: Vector2::new(400.0, 0.0),
+        }
+    }
+
+    #[test]
+    fn leaves_view_inside_bounds() {
+        let center = Vector2::new(100.0, -100.0);
+        assert_eq!(bounds().clamp(center, Vector2::new(50.0, 50.0)), center);
+    }
+
+    #[test]
+    fn keeps_view_edges_inside_bounds() {
+        let clamped = bounds().clamp(Vector2::new(10.0, 20.0), Vector2::new(50.0, 50.0));
+        assert_eq!(clamped, Vector2::new(50.0, -50.0));
+        let clamped = bounds().clamp(Vector2::new(390.0, -190.0), Vector2::new(50.0, 50.0));
+        assert_eq!(clamped, Vector2::new(350.0, -150.0));
+    }
+
+    #[test]
+    fn centers_view_larger_than_bounds() {
+        let clamped = bounds().clamp(Vector2::new(10.0, 20.0), Vector2::new(50.0, 150.0));
+        assert_eq!(clamped, Vector2::new(50.0, -100.0));
+    }
+
+    fn constants(platform_snap: bool) -> CameraFollowConstants {
+        CameraFollowConstants {
+            time_to_target: 0.3,
+            smoothing_factor: 15.0,
+            dead_zone_width: 20.0,
+            dead_zone_height: 20.0,
+            look_ahead_distance: 0.0,
+            look_ahead_secs: 0.5,
+            platform_snap,
+            min_zoom: 1.0,
+            max_zoom: 2.0,
+            framing_margin: 10.0,
+        }
+    }
+
+    #[test]
+    fn dead_zone_holds_focus() {
+        let mut tracking = TrackingCamera::default();
+        let constants = constants(false);
+        let followed = tracking.follow(
+            Vector2::new(8.0, -8.0),
+            Vector2::zeros(),
+            false,
+            &constants,
+            0.1,
+        );
+        assert_eq!(followed, Vector2::zeros());
+        let followed = tracking.follow(
+            Vector2::new(30.0, 0.0),
+            Vector2::zeros(),
+            false,
+            &constants,
+            0.1,
+        );
+        assert_eq!(followed, Vector2::new(20.0, 0.0));
+    }
+
+    #[test]
+    fn platform_snap_recenters_on_landing() {
+        let mut tracking = TrackingCamera::default();
+        let constants = constants(true);
+        let followed = tracking.follow(
+            Vector2::new(0.0, 5.0),
+            Vector2::zeros(),
+            false,
+            &constants,
+            0.1,
+        );
+        assert_eq!(followed, Vector2::zeros());
+        let followed = tracking.follow(
+            Vector2::new(0.0, 5.0),
+            Vector2::zeros(),
+            true,
+            &constants,
+            0.1,
+        );
+        assert_eq!(followed, Vector2::new(0.0, 5.0));
+    }
+
+    fn target_at(x: f32, y: f32, weight: f32) -> TargetState {
+        TargetState {
+            translation: Vector3::new(x, y, 1.0),
+            rotation: UnitQuaternion::identity(),
+            weight,
+            velocity: Vector2::zeros(),
+            grounded: Some(true),
+        }
+    }
+
+    #[test]
+    fn frames_weighted_targets() {
+        let framing =
+            frame_targets(&[target_at(0.0, 0.0, 1.0), target_at(40.0, 20.0, 3.0)]).unwrap();
+        assert_eq!(framing.translation, Vector3::new(30.0, 15.0, 1.0));
+        assert_eq!(framing.extent, Vector2::new(30.0, 15.0));
+        assert!(framing.grounded);
+    }
+
+    #[test]
+    fn frames_grounded_ignoring_targets_without_ground_state() {
+        let floating = || TargetState {
+            grounded: None,
+            ..target_at(40.0, 20.0, 1.0)
+        };
+        let framing = frame_targets(&[target_at(0.0, 0.0, 1.0), floating()]).unwrap();
+        assert!(framing.grounded);
+        assert!(!frame_targets(&[floating()]).unwrap().grounded);
+    }
+
+    #[test]
+    fn frames_unweighted_targets_evenly() {
+        let framing =
+            frame_targets(&[target_at(0.0, 0.0, 0.0), target_at(40.0, 20.0, 0.0)]).unwrap();
+        assert_eq!(framing.translation, Vector3::new(20.0, 10.0, 1.0));
+    }
+
+    #[test]
+    fn frames_nothing_without_targets() {
+        assert!(frame_targets(&[]).is_none());
+    }
+}
@@ -0,0 +1,220 @@
+use amethyst::{
+    core::{timing::Time, Transform},
+    derive::SystemDesc,
+    ecs::prelude::*,
+};
+use nalgebra::Vector2;
+
+use crate::{
+    systems::CameraBounds,
+    world::{ConstantsConfig, Player},
+};
+
+/// How the camera moves into a room the player has just entered.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RoomTransition {
+    /// Scroll from the old room to the new one.
+    Slide,
+    /// Jump straight to the new room.
+    Cut,
+}
+
+impl RoomTransition {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "slide" => Some(RoomTransition::Slide),
+            "cut" => Some(RoomTransition::Cut),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Room {
+    pub bounds: CameraBounds,
+    /// How to move into this room.
+    pub transition: RoomTransition,
+}
+
+#[derive(Clone, Debug)]
+struct RoomChange {
+    from: CameraBounds,
+    transition: RoomTransition,
+    elapsed: f32,
+}
+
+/// Rooms the camera is kept inside of, one at a time, instead of following
+/// the player across the whole map. Only present on levels which use them.
+///
+/// While the camera moves between rooms it's placed exactly rather than
+/// smoothed, and players can't be controlled.
+#[derive(Clone, Debug)]
+pub struct CameraRooms {
+    rooms: Vec<Room>,
+    current: Option<usize>,
+    bounds: Option<CameraBounds>,
+    change: Option<RoomChange>,
+}
+
+impl CameraRooms {
+    pub fn new(rooms: Vec<Room>) -> Self {
+        CameraRooms {
+            rooms,
+            current: None,
+            bounds: None,
+            change: None,
+        }
+    }
+
+    /// Where the camera is kept this frame. During a slide, this is
+    /// partway between the two rooms.
+    pub fn bounds(&self) -> Option<&CameraBounds> {
+        self.bounds.as_ref()
+    }
+
+    pub fn transitioning(&self) -> bool {
+        self.change.is_some()
+    }
+
+    /// Starts a transition if `player` has moved into another room, and
+    /// advances the current one.
+    fn update(
+        &mut self,
+        player: Option<Vector2<f32>>,
+        delta_t: f32,
+        constants: &CameraRoomsConstants,
+    ) {
+        // keep the last room while the player is between rooms.
+        let entered = player
+            .and_then(|player| self.rooms.iter().position(|room| room.bounds.contains(player)));
+        if let Some(entered) = entered {
+            if self.current != Some(entered) {
+                let room = &self.rooms[entered];
+                self.change = self.bounds.take().map(|from| RoomChange {
+                    from,
+                    transition: room.transition,
+                    elapsed: 0.0,
+                });
+                self.current = Some(entered);
+            }
+        }
+        let target = match self.current {
+            Some(current) => &self.rooms[current].bounds,
+            None => return,
+        };
+        self.bounds = Some(match &mut self.change {
+            Some(change) => {
+                change.elapsed += delta_t;
+                let duration = match change.transition {
+                    RoomTransition::Slide => constants.slide_secs,
+                    RoomTransition::Cut => constants.cut_freeze_secs,
+                };
+                let progress = if duration > 0.0 {
+                    (change.elapsed / duration).min(1.0)
+                } else {
+                    1.0
+                };
+                let bounds = match change.transition {
+                    // ease in and out.
+                    RoomTransition::Slide => {
+                        change.from.lerp(target, progress * progress * (3.0 - 2.0 * progress))
+                    }
+                    RoomTransition::Cut => target.clone(),
+                };
+                if progress >= 1.0 {
+                    self.change = None;
+                }
+                bounds
+            }
+            None => target.clone(),
+        });
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CameraRoomsConstants {
+    slide_secs: f32,
+    /// How long players can't be controlled after a cut.
+    cut_freeze_secs: f32,
+}
+
+/// Moves the camera between `CameraRooms` as the player crosses into them.
+#[derive(SystemDesc)]
+pub struct CameraRoomsSystem;
+
+impl<'s> System<'s> for CameraRoomsSystem {
+    type SystemData = (
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Player>,
+        Option<Write<'s, CameraRooms>>,
+        Read<'s, Time>,
+        ReadExpect<'s, ConstantsConfig>,
+    );
+
+    fn run(&mut self, (transforms, players, rooms, time, constants): Self::SystemData) {
+        let mut rooms = match rooms {
+            Some(v) => v,
+            None => return,
+        };
+        let player = (&transforms, &players)
+            .join()
+            .next()
+            .map(|(transform, _)| transform.translation().xy());
+        rooms.update(player, time.delta_seconds(), &constants.camera_rooms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+
+    use super::{CameraRooms, CameraRoomsConstants, Room, RoomTransition};
+    use crate::systems::CameraBounds;
+
+    fn rooms(transition: RoomTransition) -> CameraRooms {
+        let room = |min_x| Room {
+            bounds: CameraBounds {
+                min: Vector2::new(min_x, 0.0),
+                max: Vector2::new(min_x + 100.0, 100.0),
+            },
+            transition,
+        };
+        CameraRooms::new(vec![room(0.0), room(100.0)])
+    }
+
+    const CONSTANTS: CameraRoomsConstants = CameraRoomsConstants {
+        slide_secs: 1.0,
+        cut_freeze_secs: 0.5,
+    };
+
+    #[test]
+    fn starts_in_room_without_transition() {
+        let mut rooms = rooms(RoomTransition::Slide);
+        rooms.update(Some(Vector2::new(150.0, 50.0)), 0.1, &CONSTANTS);
+        assert!(!rooms.transitioning());
+        assert_eq!(rooms.bounds().unwrap().min, Vector2::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn slides_between_rooms() {
+        let mut rooms = rooms(RoomTransition::Slide);
+        rooms.update(Some(Vector2::new(50.0, 50.0)), 0.1, &CONSTANTS);
+        rooms.update(Some(Vector2::new(101.0, 50.0)), 0.5, &CONSTANTS);
+        assert!(rooms.transitioning());
+        assert_eq!(rooms.bounds().unwrap().min, Vector2::new(50.0, 0.0));
+        rooms.update(Some(Vector2::new(101.0, 50.0)), 0.5, &CONSTANTS);
+        assert!(!rooms.transitioning());
+        assert_eq!(rooms.bounds().unwrap().min, Vector2::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn cuts_and_freezes() {
+        let mut rooms = rooms(RoomTransition::Cut);
+        rooms.update(Some(Vector2::new(50.0, 50.0)), 0.1, &CONSTANTS);
+        rooms.update(Some(Vector2::new(101.0, 50.0)), 0.1, &CONSTANTS);
+        assert!(rooms.transitioning());
+        assert_eq!(rooms.bounds().unwrap().min, Vector2::new(100.0, 0.0));
+        rooms.update(None, 0.4, &CONSTANTS);
+        assert!(!rooms.transitioning());
+    }
+}
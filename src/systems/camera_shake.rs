@@ -0,0 +1,172 @@
+use std::f32::consts::PI;
+
+use amethyst::{
+    core::{timing::Time, Transform},
+    derive::SystemDesc,
+    ecs::prelude::*,
+    shred::DynamicSystemData,
+    shrev::EventChannel,
+};
+use nalgebra::Vector3;
+
+use crate::{
+    collisions::components::GroundState,
+    world::{AccessibilityConfig, ConstantsConfig, Player},
+};
+
+/// Shakes every camera with a `CameraShaker`. Send these on an
+/// `EventChannel<CameraShake>`.
+#[derive(Copy, Clone, Debug)]
+pub struct CameraShake {
+    /// Largest offset, in world units. The shake eases out from this over
+    /// `duration`.
+    pub amount: f32,
+    /// Seconds.
+    pub duration: f32,
+    /// Oscillations per second.
+    pub frequency: f32,
+}
+
+#[derive(Clone, Debug)]
+struct ActiveShake {
+    shake: CameraShake,
+    elapsed: f32,
+}
+
+impl ActiveShake {
+    fn offset(&self) -> Vector3<f32> {
+        let remaining = 1.0 - self.elapsed / self.shake.duration;
+        // square the falloff so that small shakes fade out quickly.
+        let amount = self.shake.amount * remaining * remaining;
+        let phase = 2.0 * PI * self.shake.frequency * self.elapsed;
+        // different frequencies on each axis so the shake doesn't look like
+        // it's moving along a line.
+        Vector3::new(phase.sin(), (phase * 1.3 + 1.7).sin(), 0.0) * amount
+    }
+}
+
+/// Lets a camera be shaken. The shake is an offset on top of wherever the
+/// camera would otherwise be, so it doesn't disturb the tracking camera's
+/// smoothing.
+#[derive(Clone, Debug)]
+pub struct CameraShaker {
+    offset: Vector3<f32>,
+    shakes: Vec<ActiveShake>,
+}
+
+impl Default for CameraShaker {
+    fn default() -> Self {
+        CameraShaker {
+            offset: Vector3::zeros(),
+            shakes: Vec::new(),
+        }
+    }
+}
+
+impl Component for CameraShaker {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl CameraShaker {
+    /// The offset currently applied to the camera's `Transform`.
+    pub fn offset(&self) -> Vector3<f32> {
+        self.offset
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CameraShakeConstants {
+    /// Landing faster than this shakes the camera.
+    landing_min_speed: f32,
+    /// Shake amount per unit of landing speed above `landing_min_speed`.
+    landing_amount_per_speed: f32,
+    landing_max_amount: f32,
+    landing_duration: f32,
+    landing_frequency: f32,
+}
+
+#[derive(Default)]
+pub struct CameraShakeSystem {
+    reader: Option<ReaderId<CameraShake>>,
+}
+
+impl<'s> System<'s> for CameraShakeSystem {
+    type SystemData = (
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, CameraShaker>,
+        Read<'s, EventChannel<CameraShake>>,
+        Read<'s, Time>,
+        ReadExpect<'s, AccessibilityConfig>,
+    );
+
+    fn run(
+        &mut self,
+        (mut transforms, mut shakers, events, time, accessibility): Self::SystemData,
+    ) {
+        let new_shakes: Vec<_> = events
+            .read(self.reader.as_mut().unwrap())
+            .filter(|shake| accessibility.screen_shake && shake.duration > 0.0)
+            .map(|&shake| ActiveShake {
+                shake,
+                elapsed: 0.0,
+            })
+            .collect();
+        let delta_t = time.delta_seconds();
+
+        for (transform, shaker) in (&mut transforms, &mut shakers).join() {
+            for active in &mut shaker.shakes {
+                active.elapsed += delta_t;
+            }
+            shaker.shakes.extend(new_shakes.iter().cloned());
+            shaker.shakes.retain(|active| active.elapsed < active.shake.duration);
+            if !accessibility.screen_shake {
+                shaker.shakes.clear();
+            }
+
+            let offset = shaker
+                .shakes
+                .iter()
+                .fold(Vector3::zeros(), |sum, active| sum + active.offset());
+            *transform.translation_mut() += offset - shaker.offset;
+            shaker.offset = offset;
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        <Self::SystemData as DynamicSystemData>::setup(&self.accessor(), world);
+        self.reader.replace(
+            world
+                .fetch_mut::<EventChannel<CameraShake>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Shakes the camera when a player lands from a high fall.
+#[derive(SystemDesc)]
+pub struct LandingShakeSystem;
+
+impl<'s> System<'s> for LandingShakeSystem {
+    type SystemData = (
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, GroundState>,
+        Write<'s, EventChannel<CameraShake>>,
+        ReadExpect<'s, ConstantsConfig>,
+    );
+
+    fn run(&mut self, (players, ground_states, mut shakes, constants): Self::SystemData) {
+        let constants = &constants.camera_shake;
+        for (_, ground) in (&players, &ground_states).join() {
+            let speed = match ground.landing_speed {
+                Some(v) if v > constants.landing_min_speed => v,
+                _ => continue,
+            };
+            let amount = (speed - constants.landing_min_speed) * constants.landing_amount_per_speed;
+            shakes.single_write(CameraShake {
+                amount: amount.min(constants.landing_max_amount),
+                duration: constants.landing_duration,
+                frequency: constants.landing_frequency,
+            });
+        }
+    }
+}
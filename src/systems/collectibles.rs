@@ -0,0 +1,157 @@
+use amethyst::{
+    derive::SystemDesc, ecs::prelude::*, shred::DynamicSystemData, shrev::EventChannel,
+    ui::UiText,
+};
+use hibitset::BitSet;
+use log::debug;
+
+use crate::{
+    collisions::events::{CollisionEvent, CollisionEventKind},
+    world::*,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CollectibleKind {
+    Coin,
+    Gem,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Collectible {
+    pub kind: CollectibleKind,
+}
+
+impl Component for Collectible {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Collectible counts for the currently loaded level.
+#[derive(Default, Debug)]
+pub struct Collectibles {
+    pub collected: u32,
+    pub total: u32,
+}
+
+/// The HUD text showing the current `Collectibles` count.
+pub struct CollectiblesText {
+    pub text: Entity,
+}
+
+#[derive(Default)]
+pub struct CollectPickupsSystem {
+    channel: Option<ReaderId<CollisionEvent>>,
+}
+
+impl<'s> System<'s> for CollectPickupsSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Collectible>,
+        Read<'s, EventChannel<CollisionEvent>>,
+        Write<'s, Collectibles>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, players, collectibles, events, mut counts): Self::SystemData,
+    ) {
+        // deleted entities stay alive until the end of the frame, so a pickup
+        // reported twice would otherwise be counted twice.
+        let mut collected = BitSet::new();
+        for event in events.read(self.channel.as_mut().unwrap()) {
+            if event.kind != CollisionEventKind::Started {
+                continue;
+            }
+            let (player, pickup) = if players.contains(event.a) {
+                (event.a, event.b)
+            } else {
+                (event.b, event.a)
+            };
+            if !players.contains(player)
+                || !entities.is_alive(pickup)
+                || collected.contains(pickup.id())
+            {
+                continue;
+            }
+            if let Some(collectible) = collectibles.get(pickup) {
+                debug!("picked up {:?}", collectible.kind);
+                counts.collected += 1;
+                collected.add(pickup.id());
+                entities
+                    .delete(pickup)
+                    .expect("expected collectible to be alive");
+            }
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        <Self::SystemData as DynamicSystemData>::setup(&self.accessor(), world);
+        self.channel.replace(
+            world
+                .fetch_mut::<EventChannel<CollisionEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[derive(SystemDesc)]
+pub struct CollectiblesHudSystem;
+
+impl<'s> System<'s> for CollectiblesHudSystem {
+    type SystemData = (
+        WriteStorage<'s, UiText>,
+        Read<'s, Collectibles>,
+        Option<Read<'s, CollectiblesText>>,
+    );
+
+    fn run(&mut self, (mut ui_texts, counts, hud): Self::SystemData) {
+        let hud = match hud {
+            Some(v) => v,
+            None => return,
+        };
+        if let Some(text) = ui_texts.get_mut(hud.text) {
+            let new_text = format!("{}/{}", counts.collected, counts.total);
+            if text.text != new_text {
+                text.text = new_text;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::{ecs::prelude::*, shrev::EventChannel};
+
+    use super::{CollectPickupsSystem, Collectible, CollectibleKind, Collectibles};
+    use crate::{
+        collisions::events::{CollisionEvent, CollisionEventKind},
+        world::Player,
+    };
+
+    #[test]
+    fn counts_duplicate_events_once() {
+        let mut world = World::new();
+        let mut system = CollectPickupsSystem::default();
+        System::setup(&mut system, &mut world);
+        let player = world.create_entity().with(Player::default()).build();
+        let coin = world
+            .create_entity()
+            .with(Collectible {
+                kind: CollectibleKind::Coin,
+            })
+            .build();
+        let started = CollisionEvent {
+            a: player,
+            b: coin,
+            kind: CollisionEventKind::Started,
+        };
+        world
+            .write_resource::<EventChannel<CollisionEvent>>()
+            .iter_write(vec![started, started]);
+
+        system.run_now(&world);
+        world.maintain();
+        assert_eq!(world.read_resource::<Collectibles>().collected, 1);
+        assert!(!world.is_alive(coin));
+    }
+}
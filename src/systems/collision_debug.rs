@@ -0,0 +1,177 @@
+use amethyst::{
+    core::{timing::Time, Transform},
+    ecs::prelude::*,
+    input::{InputHandler, StringBindings},
+    renderer::{debug_drawing::DebugLines, palette::Srgba},
+};
+use nalgebra::{Isometry2, Point2, Point3, Vector2};
+
+use crate::{
+    collisions::{
+        backend::{PhysicsBackend, PhysicsWorld},
+        components::GroundState,
+        geometry::{CollisionGroups, CollisionShape},
+        layers::CollisionLayer,
+        prelude::TransformExt,
+    },
+    world::*,
+};
+
+const DEBUG_LINES_Z: f32 = 0.5;
+const CIRCLE_POINTS: u32 = 16;
+const NORMAL_LENGTH: f32 = 6.0;
+
+/// Whether the collision debug overlay is drawn. Toggled with the
+/// `toggle_collision_debug` action.
+#[derive(Default)]
+pub struct CollisionDebugSettings {
+    pub enabled: bool,
+}
+
+/// Draws collision shapes, contacts, velocities and ground probes using
+/// `DebugLines`.
+#[derive(Default)]
+pub struct CollisionDebugSystem {
+    toggle_was_down: bool,
+}
+
+impl<'s> System<'s> for CollisionDebugSystem {
+    type SystemData = (
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Velocity>,
+        ReadStorage<'s, GroundState>,
+        Read<'s, PhysicsWorld>,
+        Read<'s, InputHandler<StringBindings>>,
+        Read<'s, Time>,
+        Write<'s, CollisionDebugSettings>,
+        Write<'s, DebugLines>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            transforms,
+            velocities,
+            ground_states,
+            physics_world,
+            input,
+            time,
+            mut settings,
+            mut debug_lines,
+        ): Self::SystemData,
+    ) {
+        let toggle_down = input
+            .action_is_down("toggle_collision_debug")
+            .unwrap_or(false);
+        if toggle_down && !self.toggle_was_down {
+            settings.enabled = !settings.enabled;
+        }
+        self.toggle_was_down = toggle_down;
+        if !settings.enabled {
+            return;
+        }
+
+        for handle in physics_world.objects() {
+            if let Some(obj) = physics_world.object(handle) {
+                let color = layer_color(obj.collision_groups);
+                draw_shape(&mut debug_lines, obj.shape, obj.position, color);
+            }
+        }
+
+        let contact_color = Srgba::new(1.0, 1.0, 0.0, 1.0);
+        for (_, _, contacts) in physics_world.contact_pairs() {
+            for contact in contacts {
+                debug_lines.draw_circle(to_3d(contact.world1), 0.5, 4, contact_color);
+                debug_lines.draw_line(
+                    to_3d(contact.world1),
+                    to_3d(contact.world1 + contact.normal.as_ref() * NORMAL_LENGTH),
+                    contact_color,
+                );
+            }
+        }
+
+        // the movement ApplyVelocity will sweep next frame.
+        let sweep_color = Srgba::new(0.0, 1.0, 1.0, 1.0);
+        for (transform, velocity) in (&transforms, &velocities).join() {
+            let origin = Point2::from(transform.to_2d_isometry().translation.vector);
+            debug_lines.draw_line(
+                to_3d(origin),
+                to_3d(origin + velocity.intended * time.delta_seconds()),
+                sweep_color,
+            );
+        }
+
+        for (transform, ground) in (&transforms, &ground_states).join() {
+            let origin = Point2::from(transform.to_2d_isometry().translation.vector);
+            let (normal, color) = match ground.normal {
+                Some(normal) => (normal.into_inner(), Srgba::new(0.0, 1.0, 0.0, 1.0)),
+                None => (-Vector2::y(), Srgba::new(1.0, 0.0, 0.0, 1.0)),
+            };
+            debug_lines.draw_line(to_3d(origin), to_3d(origin + normal * NORMAL_LENGTH), color);
+        }
+    }
+}
+
+fn to_3d(point: Point2<f32>) -> Point3<f32> {
+    Point3::new(point.x, point.y, DEBUG_LINES_Z)
+}
+
+fn layer_color(groups: &CollisionGroups) -> Srgba {
+    let layer = CollisionLayer::ALL
+        .iter()
+        .copied()
+        .find(|&layer| groups.is_member_of(layer.group_id()));
+    let (r, g, b) = match layer {
+        Some(CollisionLayer::Player) => (0.2, 0.6, 1.0),
+        Some(CollisionLayer::Terrain) => (1.0, 1.0, 1.0),
+        Some(CollisionLayer::Enemy) => (1.0, 0.2, 0.2),
+        Some(CollisionLayer::Pickup) => (1.0, 0.8, 0.0),
+        Some(CollisionLayer::Sensor) => (0.6, 0.2, 1.0),
+        Some(CollisionLayer::Projectile) => (1.0, 0.5, 0.0),
+        None => (0.5, 0.5, 0.5),
+    };
+    Srgba::new(r, g, b, 1.0)
+}
+
+fn draw_shape(
+    debug_lines: &mut DebugLines,
+    shape: &CollisionShape,
+    isometry: &Isometry2<f32>,
+    color: Srgba,
+) {
+    match shape {
+        CollisionShape::Cuboid { half_extents: half } => {
+            let corners = [
+                Point2::new(-half.x, -half.y),
+                Point2::new(half.x, -half.y),
+                Point2::new(half.x, half.y),
+                Point2::new(-half.x, half.y),
+            ];
+            draw_outline(debug_lines, &corners, isometry, color);
+        }
+        CollisionShape::Ball { radius } => {
+            debug_lines.draw_circle(
+                to_3d(isometry * Point2::origin()),
+                *radius,
+                CIRCLE_POINTS,
+                color,
+            );
+        }
+        CollisionShape::ConvexPolygon { points } => {
+            draw_outline(debug_lines, points, isometry, color);
+        }
+    }
+}
+
+fn draw_outline(
+    debug_lines: &mut DebugLines,
+    points: &[Point2<f32>],
+    isometry: &Isometry2<f32>,
+    color: Srgba,
+) {
+    for i in 0..points.len() {
+        let start = isometry * points[i];
+        let end = isometry * points[(i + 1) % points.len()];
+        debug_lines.draw_line(to_3d(start), to_3d(end), color);
+    }
+}
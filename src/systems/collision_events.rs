@@ -0,0 +1,22 @@
+use amethyst::{derive::SystemDesc, ecs::prelude::*, shrev::EventChannel};
+
+use crate::collisions::{
+    backend::{PhysicsBackend, PhysicsWorld},
+    events::CollisionEvent,
+};
+
+/// Publishes the physics backend's contact and proximity changes as
+/// `CollisionEvent`s on an `EventChannel`.
+#[derive(SystemDesc)]
+pub struct CollisionEventsSystem;
+
+impl<'s> System<'s> for CollisionEventsSystem {
+    type SystemData = (
+        Read<'s, PhysicsWorld>,
+        Write<'s, EventChannel<CollisionEvent>>,
+    );
+
+    fn run(&mut self, (physics_world, mut channel): Self::SystemData) {
+        channel.iter_write(physics_world.collision_events().iter().copied());
+    }
+}
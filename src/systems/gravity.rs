@@ -1,14 +1,37 @@
 use amethyst::{core::timing::Time, derive::SystemDesc, ecs::prelude::*};
 
-use super::Ncollide2dWorld;
 use crate::{
-    collisions::{
-        components::{HasGravity, Ncollide2dHandle},
-        resolution_utils::on_floor,
-    },
+    collisions::components::{GroundState, HasGravity},
     world::*,
 };
 
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FastFallConstants {
+    gravity_multiplier: f32,
+    terminal_velocity: f32,
+}
+
+/// The downward acceleration and largest fall speed for an entity. Fast
+/// falling speeds up the fall, and can raise the entity's `max_fall_speed`
+/// but never lowers it.
+fn fall_limits(
+    gravity: &HasGravity,
+    fast_falling: bool,
+    gravity_accel: f32,
+    terminal_velocity: f32,
+    fast_fall: &FastFallConstants,
+) -> (f32, f32) {
+    let max_fall_speed = gravity.max_fall_speed.unwrap_or(terminal_velocity);
+    if fast_falling {
+        (
+            gravity_accel * fast_fall.gravity_multiplier,
+            max_fall_speed.max(fast_fall.terminal_velocity),
+        )
+    } else {
+        (gravity_accel, max_fall_speed)
+    }
+}
+
 #[derive(SystemDesc)]
 pub struct GravitySystem;
 
@@ -16,20 +39,64 @@ impl<'s> System<'s> for GravitySystem {
     type SystemData = (
         WriteStorage<'s, Velocity>,
         ReadStorage<'s, HasGravity>,
-        ReadStorage<'s, Ncollide2dHandle>,
-        Read<'s, Ncollide2dWorld>,
+        ReadStorage<'s, GroundState>,
+        ReadStorage<'s, Player>,
         Read<'s, Time>,
         ReadExpect<'s, ConstantsConfig>,
     );
     fn run(
         &mut self,
-        (mut velocities, gravities, handles, ncollide_world, time, constants): Self::SystemData,
+        (mut velocities, gravities, ground_states, players, time, constants): Self::SystemData,
     ) {
-        let ncollide_world = &ncollide_world.world;
-        for (velocity, _, handle) in (&mut velocities, &gravities, &handles).join() {
-            if !on_floor(ncollide_world, handle.0) {
-                velocity.intended.y -= constants.gravity_accel * time.delta_seconds();
+        for (velocity, gravity, ground, player) in (
+            &mut velocities,
+            &gravities,
+            ground_states.maybe(),
+            players.maybe(),
+        )
+            .join()
+        {
+            // entities which don't probe for ground are always falling.
+            if ground.map_or(false, |ground| ground.grounded) {
+                continue;
             }
+            let fast_falling = player.map_or(false, |p| p.fast_falling);
+            let (accel, max_fall_speed) = fall_limits(
+                gravity,
+                fast_falling,
+                constants.gravity_accel,
+                constants.terminal_velocity,
+                &constants.fast_fall,
+            );
+            velocity.intended.y -= accel * gravity.scale * time.delta_seconds();
+            velocity.intended.y = velocity.intended.y.max(-max_fall_speed);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{fall_limits, FastFallConstants};
+    use crate::collisions::components::HasGravity;
+
+    const FAST_FALL: FastFallConstants = FastFallConstants {
+        gravity_multiplier: 2.0,
+        terminal_velocity: 600.0,
+    };
+
+    #[test]
+    fn fast_fall_uses_global_cap() {
+        let limits = fall_limits(&HasGravity::default(), true, 1000.0, 400.0, &FAST_FALL);
+        assert_eq!(limits, (2000.0, 600.0));
+    }
+
+    #[test]
+    fn fast_fall_keeps_higher_entity_cap() {
+        let gravity = HasGravity {
+            scale: 1.0,
+            max_fall_speed: Some(800.0),
+        };
+        assert_eq!(fall_limits(&gravity, false, 1000.0, 400.0, &FAST_FALL), (1000.0, 800.0));
+        assert_eq!(fall_limits(&gravity, true, 1000.0, 400.0, &FAST_FALL), (2000.0, 800.0));
+    }
+}
@@ -0,0 +1,68 @@
+use amethyst::{core::timing::Time, derive::SystemDesc, ecs::prelude::*};
+use nalgebra::Vector2;
+
+use crate::{
+    collisions::{
+        backend::PhysicsWorld,
+        components::{GroundState, PhysicsHandle},
+        resolution_utils::floor_contacts,
+    },
+    world::*,
+};
+
+#[derive(SystemDesc)]
+pub struct GroundProbeSystem;
+
+impl<'s> System<'s> for GroundProbeSystem {
+    type SystemData = (
+        WriteStorage<'s, GroundState>,
+        ReadStorage<'s, PhysicsHandle>,
+        ReadStorage<'s, Surface>,
+        ReadStorage<'s, Velocity>,
+        Read<'s, PhysicsWorld>,
+        Read<'s, Time>,
+    );
+
+    fn run(
+        &mut self,
+        (mut ground_states, handles, surfaces, velocities, physics_world, time): Self::SystemData,
+    ) {
+        let physics_world = &*physics_world;
+        let down = -Vector2::y_axis();
+        for (ground, handle, velocity) in
+            (&mut ground_states, &handles, velocities.maybe()).join()
+        {
+            // prefer the flattest ground when standing on several objects.
+            let nearest = floor_contacts(physics_world, handle.0, down).max_by(|(_, c1), (_, c2)| {
+                let flatness1 = c1.normal.dot(down.as_ref());
+                let flatness2 = c2.normal.dot(down.as_ref());
+                flatness1.partial_cmp(&flatness2).unwrap()
+            });
+            match nearest {
+                Some((entity, contact)) => {
+                    ground.landing_speed = if ground.grounded {
+                        None
+                    } else {
+                        Some(ground.fall_speed)
+                    };
+                    ground.fall_speed = 0.0;
+                    ground.grounded = true;
+                    ground.normal = Some(-contact.normal);
+                    ground.entity = Some(entity);
+                    ground.surface = surfaces.get(entity).cloned();
+                    ground.time_since_grounded = 0.0;
+                }
+                None => {
+                    ground.grounded = false;
+                    ground.normal = None;
+                    ground.entity = None;
+                    ground.surface = None;
+                    ground.time_since_grounded += time.delta_seconds();
+                    let falling_at = velocity.map_or(0.0, |v| -v.intended.y);
+                    ground.fall_speed = ground.fall_speed.max(falling_at);
+                    ground.landing_speed = None;
+                }
+            }
+        }
+    }
+}
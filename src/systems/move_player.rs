@@ -6,11 +6,7 @@ use amethyst::{
 };
 use log::debug;
 
-use super::Ncollide2dWorld;
-use crate::{
-    collisions::{components::Ncollide2dHandle, resolution_utils::on_floor},
-    world::*,
-};
+use crate::{collisions::components::GroundState, systems::CameraRooms, world::*};
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct PlayerMovementConstants {
@@ -32,11 +28,11 @@ impl<'s> System<'s> for MovePlayerSystem {
         WriteStorage<'s, Transform>,
         WriteStorage<'s, Velocity>,
         WriteStorage<'s, Player>,
-        ReadStorage<'s, Ncollide2dHandle>,
+        ReadStorage<'s, GroundState>,
         Read<'s, InputHandler<StringBindings>>,
-        Read<'s, Ncollide2dWorld>,
         Read<'s, Time>,
         ReadExpect<'s, ConstantsConfig>,
+        Option<Read<'s, CameraRooms>>,
     );
     fn run(
         &mut self,
@@ -44,31 +40,43 @@ impl<'s> System<'s> for MovePlayerSystem {
             mut transforms,
             mut velocities,
             mut players,
-            handles,
+            ground_states,
             input,
-            ncollide_world,
             time,
             constants,
+            rooms,
         ): Self::SystemData,
     ) {
         let constants = &constants.player;
-        let ncollide_world = &ncollide_world.world;
         let delta_t = time.delta_seconds();
-        for (transform, velocity, player, handle) in
-            (&mut transforms, &mut velocities, &mut players, &handles).join()
+        let air = Surface::default();
+        // players can't be controlled while the camera moves between rooms.
+        let frozen = rooms.map_or(false, |rooms| rooms.transitioning());
+        for (transform, velocity, player, ground) in
+            (&mut transforms, &mut velocities, &mut players, &ground_states).join()
         {
-            let lr = input.axis_value("left_right");
-            let jump = input.action_is_down("jump");
-            let on_floor = on_floor(ncollide_world, handle.0);
+            let lr = input
+                .axis_value("left_right")
+                .map(|lr| if frozen { 0.0 } else { lr });
+            let jump = input.action_is_down("jump").filter(|_| !frozen);
+            let down = input.action_is_down("down").unwrap_or(false) && !frozen;
+            let on_floor = ground.grounded;
+            let surface = ground.surface.as_ref().unwrap_or(&air);
             if let Some(lr) = lr {
                 velocity.intended.x += lr as f32
                     * constants.horizontal_acceleration
+                    * surface.acceleration
                     * delta_t
                     * (1.0 + player.air_boost);
-                velocity.intended.x = velocity.intended.x
-                    * (1.0 - delta_t / constants.horizontal_decrease_secs_per_decrease)
-                    + (velocity.intended.x * constants.horizontal_decrease_multiplier)
-                        * (delta_t / constants.horizontal_decrease_secs_per_decrease);
+                // decrease speed relative to the ground, so that conveyors
+                // carry us along at their own speed.
+                let decrease = (delta_t / constants.horizontal_decrease_secs_per_decrease
+                    * surface.friction)
+                    .min(1.0);
+                let relative = velocity.intended.x - surface.push;
+                velocity.intended.x = surface.push
+                    + relative * (1.0 - decrease)
+                    + (relative * constants.horizontal_decrease_multiplier) * decrease;
             }
             // jumping
             if jump.unwrap_or(false) {
@@ -79,6 +87,7 @@ impl<'s> System<'s> for MovePlayerSystem {
                     debug!("jumping but not on floor");
                 }
             }
+            player.fast_falling = down && !on_floor;
             // air boost
             if on_floor {
                 player.air_boost =
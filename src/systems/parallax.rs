@@ -0,0 +1,63 @@
+use amethyst::{core::Transform, derive::SystemDesc, ecs::prelude::*, renderer::Camera};
+use nalgebra::{Vector2, Vector3};
+
+/// Scenery which scrolls at its own speed relative to the camera.
+#[derive(Clone, Debug)]
+pub struct Parallax {
+    /// How far the scenery moves on screen for each unit the camera moves.
+    /// 1.0 moves with the world, smaller values look further away, and 0.0
+    /// stays still on screen.
+    pub factor: Vector2<f32>,
+    /// Where the scenery is when the camera is at the origin.
+    pub origin: Vector3<f32>,
+}
+
+impl Component for Parallax {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Parallax {
+    fn translation(&self, camera: Vector2<f32>) -> Vector3<f32> {
+        let offset = camera.component_mul(&(Vector2::repeat(1.0) - self.factor));
+        self.origin + Vector3::new(offset.x, offset.y, 0.0)
+    }
+}
+
+/// Moves `Parallax` scenery to match the first camera.
+#[derive(SystemDesc)]
+pub struct ParallaxSystem;
+
+impl<'s> System<'s> for ParallaxSystem {
+    type SystemData = (
+        WriteStorage<'s, Transform>,
+        ReadStorage<'s, Parallax>,
+        ReadStorage<'s, Camera>,
+    );
+
+    fn run(&mut self, (mut transforms, parallaxes, cameras): Self::SystemData) {
+        let camera = match (&cameras, &transforms).join().next() {
+            Some((_, transform)) => transform.translation().xy(),
+            None => return,
+        };
+        for (transform, parallax) in (&mut transforms, &parallaxes).join() {
+            transform.set_translation(parallax.translation(camera));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Vector2, Vector3};
+
+    use super::Parallax;
+
+    #[test]
+    fn scrolls_slower_than_camera() {
+        let parallax = Parallax {
+            factor: Vector2::new(0.5, 1.0),
+            origin: Vector3::new(16.0, 32.0, -0.5),
+        };
+        let translation = parallax.translation(Vector2::new(100.0, 50.0));
+        assert_eq!(translation, Vector3::new(66.0, 32.0, -0.5));
+    }
+}
@@ -0,0 +1,562 @@
+use std::collections::{BTreeMap, HashMap};
+
+use amethyst::{
+    core::Transform,
+    ecs::{
+        prelude::*,
+        world::{EntitiesRes, Index},
+    },
+    shred::DynamicSystemData,
+};
+use nalgebra::Vector2;
+
+use crate::collisions::{
+    backend::{BackendHandle, PhysicsBackend, PhysicsWorld},
+    components::{CollisionPresence, PhysicsHandle},
+    prelude::TransformExt,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ChangeType {
+    None,
+    Inserted,
+    Modified,
+    Removed,
+}
+impl Default for ChangeType {
+    fn default() -> Self {
+        ChangeType::None
+    }
+}
+impl ChangeType {
+    fn and(self, other: ChangeType) -> ChangeType {
+        use ChangeType::*;
+        match (self, other) {
+            (None, x) => x,
+            (x, None) => x,
+            (Inserted, Inserted) => panic!("bad change combination"),
+            (Inserted, Modified) => Inserted,
+            (Inserted, Removed) => None,
+            (Modified, Inserted) => panic!("bad change combination"),
+            (Modified, Modified) => Modified,
+            (Modified, Removed) => Removed,
+            (Removed, Inserted) => Modified,
+            (Removed, _) => panic!("bad change combination"),
+        }
+    }
+
+    fn add_change(&mut self, other: ChangeType) {
+        *self = self.and(other);
+    }
+
+    fn of(change: ComponentEvent) -> Self {
+        match change {
+            ComponentEvent::Inserted(_) => ChangeType::Inserted,
+            ComponentEvent::Modified(_) => ChangeType::Modified,
+            ComponentEvent::Removed(_) => ChangeType::Removed,
+        }
+    }
+}
+trait ComponentEventExt {
+    fn idx(self) -> Index;
+}
+impl ComponentEventExt for ComponentEvent {
+    fn idx(self) -> Index {
+        match self {
+            ComponentEvent::Inserted(idx) => idx,
+            ComponentEvent::Modified(idx) => idx,
+            ComponentEvent::Removed(idx) => idx,
+        }
+    }
+}
+/// Pending changes to the two components mirrored into the `PhysicsWorld`.
+#[derive(Copy, Clone, Default)]
+struct Changes {
+    presence: ChangeType,
+    transform: ChangeType,
+}
+
+fn read_changes<'a>(
+    events: impl Iterator<Item = &'a ComponentEvent>,
+    changes: &mut BTreeMap<Index, Changes>,
+    select: impl Fn(&mut Changes) -> &mut ChangeType,
+) {
+    for change in events {
+        select(changes.entry(change.idx()).or_default()).add_change(ChangeType::of(*change));
+    }
+}
+
+/// A collision object added for an entity.
+struct TrackedObject {
+    entity: Entity,
+    handle: BackendHandle,
+    /// The `Transform` scale the object's shape was last built with.
+    scale: Vector2<f32>,
+}
+
+/// Keeps the `PhysicsWorld` in sync with `CollisionPresence` and
+/// `Transform`.
+///
+/// An entity has a collision object exactly when it is alive and has both
+/// components. This is driven by the change channels of both storages, so
+/// only entities which changed since the last run are looked at.
+#[derive(Default)]
+pub struct PhysicsSyncSystem {
+    presences_channel: Option<ReaderId<ComponentEvent>>,
+    transforms_channel: Option<ReaderId<ComponentEvent>>,
+    objects: HashMap<Index, TrackedObject>,
+}
+
+impl<'s> System<'s> for PhysicsSyncSystem {
+    type SystemData = (
+        Read<'s, EntitiesRes>,
+        Write<'s, PhysicsWorld>,
+        WriteStorage<'s, PhysicsHandle>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, CollisionPresence>,
+    );
+
+    fn run(&mut self, (entities, mut world, mut handles, transforms, presences): Self::SystemData) {
+        let mut changes = BTreeMap::<Index, Changes>::new();
+        read_changes(
+            presences
+                .channel()
+                .read(self.presences_channel.as_mut().unwrap()),
+            &mut changes,
+            |c| &mut c.presence,
+        );
+        read_changes(
+            transforms
+                .channel()
+                .read(self.transforms_channel.as_mut().unwrap()),
+            &mut changes,
+            |c| &mut c.transform,
+        );
+
+        for (idx, change) in changes {
+            let entity = entities.entity(idx);
+            // Deleted entities lose their `PhysicsHandle` along with
+            // everything else, so we keep our own record of which entity owns
+            // each object. This also catches an index which was freed and
+            // reused by a new entity since the last run.
+            if let Some(tracked) = self.objects.get(&idx) {
+                if tracked.entity != entity || !entities.is_alive(entity) {
+                    world.remove(tracked.handle);
+                    self.objects.remove(&idx);
+                }
+            }
+            let wanted = match (transforms.get(entity), presences.get(entity)) {
+                (Some(transform), Some(presence)) => Some((transform, presence)),
+                _ => None,
+            };
+            match (wanted, self.objects.get_mut(&idx)) {
+                (Some((transform, presence)), None) => {
+                    let scale = transform.scale_2d();
+                    let handle = world.add(
+                        transform.to_2d_isometry(),
+                        presence.scaled_shape(scale),
+                        presence.collision_groups,
+                        presence.query_type,
+                        entity,
+                    );
+                    self.objects.insert(
+                        idx,
+                        TrackedObject {
+                            entity,
+                            handle,
+                            scale,
+                        },
+                    );
+                    handles
+                        .insert(entity, PhysicsHandle(handle))
+                        .expect("expected entity with components to be alive");
+                }
+                (Some((transform, presence)), Some(tracked)) => {
+                    let presence_changed = match change.presence {
+                        ChangeType::Inserted | ChangeType::Modified => true,
+                        ChangeType::None | ChangeType::Removed => false,
+                    };
+                    let scale = transform.scale_2d();
+                    // TODO: avoid setting all update flags (complicated by the
+                    // fact that none of the data structures involved implement
+                    // PartialEq)
+                    if presence_changed || scale != tracked.scale {
+                        world.set_shape(tracked.handle, presence.scaled_shape(scale));
+                        tracked.scale = scale;
+                    }
+                    if presence_changed {
+                        world.set_collision_groups(tracked.handle, presence.collision_groups);
+                        world.set_query_type(tracked.handle, presence.query_type);
+                    }
+                    if let ChangeType::Inserted | ChangeType::Modified = change.transform {
+                        world.set_position(tracked.handle, transform.to_2d_isometry());
+                    }
+                }
+                (None, Some(tracked)) => {
+                    world.remove(tracked.handle);
+                    self.objects.remove(&idx);
+                    // drop the handle too, so that a later re-insertion is
+                    // treated as an addition.
+                    handles.remove(entity);
+                }
+                (None, None) => (),
+            }
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        // copied from default impl
+        <Self::SystemData as DynamicSystemData>::setup(&self.accessor(), world);
+        // populate channels
+        let mut presences = <WriteStorage<'_, CollisionPresence> as SystemData>::fetch(&world);
+        self.presences_channel.replace(presences.register_reader());
+        let mut transforms = <WriteStorage<'_, Transform> as SystemData>::fetch(&world);
+        self.transforms_channel.replace(transforms.register_reader());
+    }
+}
+
+#[derive(Default)]
+pub struct PhysicsUpdateWorldSystem;
+
+impl<'s> System<'s> for PhysicsUpdateWorldSystem {
+    type SystemData = (Write<'s, PhysicsWorld>,);
+
+    fn run(&mut self, (mut world,): Self::SystemData) {
+        world.update();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::{core::Transform, ecs::prelude::*};
+    use nalgebra::Vector2;
+
+    use super::{ChangeType, PhysicsSyncSystem};
+    use crate::collisions::{
+        backend::{PhysicsBackend, PhysicsWorld},
+        components::{CollisionPresence, PhysicsHandle},
+        geometry::{CollisionGroups, CollisionShape, QueryType},
+    };
+
+    #[test]
+    fn change_type_transitions() {
+        use ChangeType::*;
+        let cases = [
+            (None, None, None),
+            (None, Inserted, Inserted),
+            (None, Modified, Modified),
+            (None, Removed, Removed),
+            (Inserted, None, Inserted),
+            (Inserted, Modified, Inserted),
+            (Inserted, Removed, None),
+            (Modified, None, Modified),
+            (Modified, Modified, Modified),
+            (Modified, Removed, Removed),
+            (Removed, None, Removed),
+            (Removed, Inserted, Modified),
+        ];
+        for &(first, second, expected) in &cases {
+            assert_eq!(
+                first.and(second),
+                expected,
+                "{:?} followed by {:?}",
+                first,
+                second
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn change_type_inserted_twice() {
+        ChangeType::Inserted.and(ChangeType::Inserted);
+    }
+
+    #[test]
+    #[should_panic]
+    fn change_type_inserted_after_modified() {
+        ChangeType::Modified.and(ChangeType::Inserted);
+    }
+
+    #[test]
+    #[should_panic]
+    fn change_type_modified_after_removed() {
+        ChangeType::Removed.and(ChangeType::Modified);
+    }
+
+    #[test]
+    #[should_panic]
+    fn change_type_removed_twice() {
+        ChangeType::Removed.and(ChangeType::Removed);
+    }
+
+    fn presence() -> CollisionPresence {
+        CollisionPresence {
+            shape: CollisionShape::cuboid(Vector2::new(8.0, 8.0)),
+            collision_groups: CollisionGroups::new(),
+            query_type: QueryType::Contacts { prediction: 0.0 },
+        }
+    }
+
+    fn transform_at(x: f32, y: f32) -> Transform {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(x, y, 0.0);
+        transform
+    }
+
+    fn setup() -> (World, PhysicsSyncSystem) {
+        let mut world = World::new();
+        let mut system = PhysicsSyncSystem::default();
+        System::setup(&mut system, &mut world);
+        (world, system)
+    }
+
+    fn step(world: &mut World, system: &mut PhysicsSyncSystem) {
+        system.run_now(world);
+        world.maintain();
+    }
+
+    fn object_count(world: &World) -> usize {
+        world.read_resource::<PhysicsWorld>().objects().len()
+    }
+
+    #[test]
+    fn adds_object_with_both_components() {
+        let (mut world, mut system) = setup();
+        let entity = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(1.0, 2.0))
+            .build();
+        step(&mut world, &mut system);
+
+        assert_eq!(object_count(&world), 1);
+        let handle = world.read_storage::<PhysicsHandle>().get(entity).unwrap().0;
+        let physics_world = world.read_resource::<PhysicsWorld>();
+        let object = physics_world.object(handle).unwrap();
+        assert_eq!(object.entity, entity);
+        assert_eq!(object.position.translation.vector, Vector2::new(1.0, 2.0));
+    }
+
+    #[test]
+    fn ignores_entities_with_one_component() {
+        let (mut world, mut system) = setup();
+        world.create_entity().with(presence()).build();
+        world.create_entity().with(transform_at(0.0, 0.0)).build();
+        step(&mut world, &mut system);
+
+        assert_eq!(object_count(&world), 0);
+    }
+
+    #[test]
+    fn adds_object_when_second_component_arrives() {
+        let (mut world, mut system) = setup();
+        let entity = world.create_entity().with(presence()).build();
+        step(&mut world, &mut system);
+        assert_eq!(object_count(&world), 0);
+
+        world
+            .write_storage::<Transform>()
+            .insert(entity, transform_at(0.0, 0.0))
+            .unwrap();
+        step(&mut world, &mut system);
+        assert_eq!(object_count(&world), 1);
+    }
+
+    #[test]
+    fn syncs_modified_transform() {
+        let (mut world, mut system) = setup();
+        let entity = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(0.0, 0.0))
+            .build();
+        step(&mut world, &mut system);
+
+        world
+            .write_storage::<Transform>()
+            .get_mut(entity)
+            .unwrap()
+            .set_translation_xyz(5.0, -3.0, 0.0);
+        step(&mut world, &mut system);
+
+        let handle = world.read_storage::<PhysicsHandle>().get(entity).unwrap().0;
+        let physics_world = world.read_resource::<PhysicsWorld>();
+        let object = physics_world.object(handle).unwrap();
+        assert_eq!(object.position.translation.vector, Vector2::new(5.0, -3.0));
+    }
+
+    #[test]
+    fn syncs_modified_presence() {
+        let (mut world, mut system) = setup();
+        let entity = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(0.0, 0.0))
+            .build();
+        step(&mut world, &mut system);
+
+        world
+            .write_storage::<CollisionPresence>()
+            .get_mut(entity)
+            .unwrap()
+            .shape = CollisionShape::Ball { radius: 3.0 };
+        step(&mut world, &mut system);
+
+        let handle = world.read_storage::<PhysicsHandle>().get(entity).unwrap().0;
+        let physics_world = world.read_resource::<PhysicsWorld>();
+        let object = physics_world.object(handle).unwrap();
+        assert_eq!(*object.shape, CollisionShape::Ball { radius: 3.0 });
+    }
+
+    #[test]
+    fn scales_shape_with_transform() {
+        let (mut world, mut system) = setup();
+        let mut transform = transform_at(0.0, 0.0);
+        transform.set_scale([2.0, -0.5, 1.0].into());
+        let entity = world.create_entity().with(presence()).with(transform).build();
+        step(&mut world, &mut system);
+
+        let half_extents = |world: &World| {
+            let handle = world.read_storage::<PhysicsHandle>().get(entity).unwrap().0;
+            let physics_world = world.read_resource::<PhysicsWorld>();
+            let object = physics_world.object(handle).unwrap();
+            match *object.shape {
+                CollisionShape::Cuboid { half_extents } => half_extents,
+                _ => panic!("expected a cuboid"),
+            }
+        };
+        assert_eq!(half_extents(&world), Vector2::new(16.0, 4.0));
+
+        world
+            .write_storage::<Transform>()
+            .get_mut(entity)
+            .unwrap()
+            .set_scale([1.0, 1.0, 1.0].into());
+        step(&mut world, &mut system);
+        assert_eq!(half_extents(&world), Vector2::new(8.0, 8.0));
+    }
+
+    #[test]
+    fn removes_object_when_presence_removed() {
+        let (mut world, mut system) = setup();
+        let entity = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(0.0, 0.0))
+            .build();
+        step(&mut world, &mut system);
+
+        world.write_storage::<CollisionPresence>().remove(entity);
+        step(&mut world, &mut system);
+
+        assert_eq!(object_count(&world), 0);
+        assert!(!world.read_storage::<PhysicsHandle>().contains(entity));
+    }
+
+    #[test]
+    fn removes_object_when_transform_removed() {
+        let (mut world, mut system) = setup();
+        let entity = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(0.0, 0.0))
+            .build();
+        step(&mut world, &mut system);
+
+        world.write_storage::<Transform>().remove(entity);
+        step(&mut world, &mut system);
+
+        assert_eq!(object_count(&world), 0);
+        assert!(!world.read_storage::<PhysicsHandle>().contains(entity));
+    }
+
+    #[test]
+    fn readds_object_after_removal() {
+        let (mut world, mut system) = setup();
+        let entity = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(0.0, 0.0))
+            .build();
+        step(&mut world, &mut system);
+
+        let removed = world
+            .write_storage::<CollisionPresence>()
+            .remove(entity)
+            .unwrap();
+        step(&mut world, &mut system);
+        world
+            .write_storage::<CollisionPresence>()
+            .insert(entity, removed)
+            .unwrap();
+        step(&mut world, &mut system);
+
+        assert_eq!(object_count(&world), 1);
+        assert!(world.read_storage::<PhysicsHandle>().contains(entity));
+    }
+
+    #[test]
+    fn remove_and_reinsert_in_one_frame_keeps_object() {
+        let (mut world, mut system) = setup();
+        let entity = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(0.0, 0.0))
+            .build();
+        step(&mut world, &mut system);
+
+        {
+            let mut presences = world.write_storage::<CollisionPresence>();
+            let removed = presences.remove(entity).unwrap();
+            presences.insert(entity, removed).unwrap();
+        }
+        step(&mut world, &mut system);
+
+        assert_eq!(object_count(&world), 1);
+    }
+
+    #[test]
+    fn removes_object_when_entity_deleted() {
+        let (mut world, mut system) = setup();
+        let entity = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(0.0, 0.0))
+            .build();
+        step(&mut world, &mut system);
+
+        world.delete_entity(entity).unwrap();
+        world.maintain();
+        step(&mut world, &mut system);
+
+        assert_eq!(object_count(&world), 0);
+    }
+
+    #[test]
+    fn replaces_object_of_deleted_entity_with_reused_index() {
+        let (mut world, mut system) = setup();
+        let old = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(0.0, 0.0))
+            .build();
+        step(&mut world, &mut system);
+
+        world.delete_entity(old).unwrap();
+        world.maintain();
+        let new = world
+            .create_entity()
+            .with(presence())
+            .with(transform_at(4.0, 4.0))
+            .build();
+        assert_eq!(old.id(), new.id());
+        step(&mut world, &mut system);
+
+        assert_eq!(object_count(&world), 1);
+        let physics_world = world.read_resource::<PhysicsWorld>();
+        let handle = physics_world.objects()[0];
+        let object = physics_world.object(handle).unwrap();
+        assert_eq!(object.entity, new);
+    }
+}
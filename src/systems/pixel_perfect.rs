@@ -0,0 +1,146 @@
+use amethyst::{
+    core::Transform, derive::SystemDesc, ecs::prelude::*, renderer::Camera,
+    window::ScreenDimensions,
+};
+use nalgebra::{Vector2, Vector3};
+
+use crate::{systems::TrackingCamera, world::ConstantsConfig};
+
+/// Keeps a camera's view an integer number of screen pixels per texel, with
+/// its edges on whole texels, so that tiles don't shimmer as it moves.
+///
+/// Like `CameraShaker`, the snap is an offset on top of wherever the camera
+/// would otherwise be.
+#[derive(Clone, Debug)]
+pub struct PixelPerfectCamera {
+    snap: Vector3<f32>,
+}
+
+impl Default for PixelPerfectCamera {
+    fn default() -> Self {
+        PixelPerfectCamera {
+            snap: Vector3::zeros(),
+        }
+    }
+}
+
+impl Component for PixelPerfectCamera {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl PixelPerfectCamera {
+    /// The offset currently applied to the camera's `Transform`.
+    pub fn snap(&self) -> Vector3<f32> {
+        self.snap
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PixelPerfectConstants {
+    enabled: bool,
+    /// The smallest area, in texels, the camera shows. The scale is the
+    /// largest whole number of pixels per texel which still shows this much.
+    view_width: f32,
+    view_height: f32,
+}
+
+/// The largest whole number of screen pixels per texel which fits `view`
+/// texels on `screen`, and at least 1.
+fn pixel_scale(screen: Vector2<f32>, view: Vector2<f32>) -> f32 {
+    screen.component_div(&view).min().floor().max(1.0)
+}
+
+/// Moves `center` so that the bottom left corner of a view with the given
+/// half extents is on a whole texel.
+fn snap_to_texels(center: Vector2<f32>, half_view: Vector2<f32>) -> Vector2<f32> {
+    (center - half_view).map(f32::round) + half_view
+}
+
+#[derive(SystemDesc)]
+pub struct PixelPerfectCameraSystem;
+
+impl<'s> System<'s> for PixelPerfectCameraSystem {
+    type SystemData = (
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Camera>,
+        WriteStorage<'s, PixelPerfectCamera>,
+        WriteStorage<'s, TrackingCamera>,
+        ReadExpect<'s, ScreenDimensions>,
+        ReadExpect<'s, ConstantsConfig>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut transforms,
+            mut cameras,
+            mut pixel_cameras,
+            mut tracking_cameras,
+            screen,
+            constants,
+        ): Self::SystemData,
+    ) {
+        let constants = &constants.pixel_perfect;
+        let screen = Vector2::new(screen.width(), screen.height());
+        let scale = pixel_scale(screen, Vector2::new(constants.view_width, constants.view_height));
+
+        for (transform, camera, pixel_camera, tracking) in (
+            &mut transforms,
+            &mut cameras,
+            &mut pixel_cameras,
+            (&mut tracking_cameras).maybe(),
+        )
+            .join()
+        {
+            let unsnapped = *transform.translation() - pixel_camera.snap;
+            if !constants.enabled {
+                transform.set_translation(unsnapped);
+                pixel_camera.snap = Vector3::zeros();
+                continue;
+            }
+            // recomputed every frame, so the view follows the window when it
+            // is resized.
+            let base_half_view = screen / scale / 2.0;
+            let zoom = match tracking {
+                Some(tracking) => {
+                    tracking.set_base_view(base_half_view);
+                    tracking.zoom()
+                }
+                None => 1.0,
+            };
+            // zoom in whole pixels per texel, too.
+            let pixels_per_texel = (scale / zoom).round().max(1.0);
+            let half_view = screen / pixels_per_texel / 2.0;
+            if let Some(ortho) = camera.projection_mut().as_orthographic_mut() {
+                ortho.set_left_and_right(-half_view.x, half_view.x);
+                ortho.set_bottom_and_top(-half_view.y, half_view.y);
+            }
+
+            let snapped = snap_to_texels(unsnapped.xy(), half_view);
+            pixel_camera.snap = Vector3::new(snapped.x, snapped.y, unsnapped.z) - unsnapped;
+            transform.set_translation(unsnapped + pixel_camera.snap);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+
+    use super::{pixel_scale, snap_to_texels};
+
+    #[test]
+    fn picks_largest_whole_scale() {
+        let view = Vector2::new(100.0, 100.0);
+        assert_eq!(pixel_scale(Vector2::new(640.0, 640.0), view), 6.0);
+        assert_eq!(pixel_scale(Vector2::new(1280.0, 720.0), view), 7.0);
+        assert_eq!(pixel_scale(Vector2::new(64.0, 64.0), view), 1.0);
+    }
+
+    #[test]
+    fn snaps_view_corner_to_texels() {
+        let half_view = Vector2::new(53.5, 40.0);
+        let snapped = snap_to_texels(Vector2::new(10.2, -3.7), half_view);
+        assert_eq!(snapped, Vector2::new(10.5, -4.0));
+    }
+}
@@ -0,0 +1,228 @@
+use amethyst::{
+    core::{timing::Time, Hidden, Transform},
+    derive::SystemDesc,
+    ecs::prelude::*,
+};
+use hibitset::BitSet;
+use nalgebra::{Vector2, Vector3};
+
+use crate::{
+    collisions::{
+        backend::PhysicsWorld,
+        components::{CollisionPresence, PhysicsHandle},
+        resolution_utils::floor_contacts,
+    },
+    world::*,
+};
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PlatformConstants {
+    crumble_delay_secs: f32,
+    crumble_respawn_secs: f32,
+    crumble_shake_amplitude: f32,
+    crumble_shake_frequency: f32,
+    timed_beat_secs: f32,
+}
+
+#[derive(Clone)]
+enum CrumbleState {
+    Solid,
+    Shaking { elapsed: f32, rest: Vector3<f32> },
+    Crumbled { elapsed: f32 },
+}
+
+/// A platform which falls apart shortly after a player lands on it, and
+/// comes back some time later.
+#[derive(Clone)]
+pub struct CrumblingPlatform {
+    state: CrumbleState,
+    presence: Option<CollisionPresence>,
+    /// Whether the platform was already hidden, by its layer, before it
+    /// crumbled.
+    was_hidden: bool,
+}
+
+impl Component for CrumblingPlatform {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Default for CrumblingPlatform {
+    fn default() -> Self {
+        CrumblingPlatform {
+            state: CrumbleState::Solid,
+            presence: None,
+            was_hidden: false,
+        }
+    }
+}
+
+/// A platform which is solid on every other beat of a global clock.
+#[derive(Clone, Default)]
+pub struct TimedPlatform {
+    /// Number of beats this platform is offset by.
+    pub phase: i32,
+    presence: Option<CollisionPresence>,
+    /// Whether the platform was already hidden, by its layer, before it
+    /// last disappeared.
+    was_hidden: bool,
+}
+
+impl Component for TimedPlatform {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl TimedPlatform {
+    pub fn new(phase: i32) -> Self {
+        TimedPlatform {
+            phase,
+            presence: None,
+            was_hidden: false,
+        }
+    }
+}
+
+#[derive(SystemDesc)]
+pub struct CrumblingPlatformSystem;
+
+impl<'s> System<'s> for CrumblingPlatformSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, CrumblingPlatform>,
+        WriteStorage<'s, CollisionPresence>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Hidden>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, PhysicsHandle>,
+        Read<'s, PhysicsWorld>,
+        Read<'s, Time>,
+        ReadExpect<'s, ConstantsConfig>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut crumblings,
+            mut presences,
+            mut transforms,
+            mut hiddens,
+            players,
+            handles,
+            physics_world,
+            time,
+            constants,
+        ): Self::SystemData,
+    ) {
+        let constants = &constants.platforms;
+        let delta_t = time.delta_seconds();
+
+        let mut landed_on = BitSet::new();
+        for (_, handle) in (&players, &handles).join() {
+            for (entity, _) in floor_contacts(&*physics_world, handle.0, -Vector2::y_axis()) {
+                landed_on.add(entity.id());
+            }
+        }
+
+        // transforms are flagged, so only touch them while shaking, rather
+        // than having every idle platform re-synced with the physics world.
+        for (entity, crumbling) in (&entities, &mut crumblings).join() {
+            crumbling.state = match crumbling.state.clone() {
+                CrumbleState::Solid => match transforms.get(entity) {
+                    Some(transform) if landed_on.contains(entity.id()) => CrumbleState::Shaking {
+                        elapsed: 0.0,
+                        rest: *transform.translation(),
+                    },
+                    _ => CrumbleState::Solid,
+                },
+                CrumbleState::Shaking { elapsed, rest } => {
+                    let transform = match transforms.get_mut(entity) {
+                        Some(v) => v,
+                        None => continue,
+                    };
+                    let elapsed = elapsed + delta_t;
+                    if elapsed >= constants.crumble_delay_secs {
+                        transform.set_translation(rest);
+                        crumbling.presence = presences.remove(entity);
+                        crumbling.was_hidden = hiddens
+                            .insert(entity, Hidden)
+                            .expect("expected crumbling platform to be alive")
+                            .is_some();
+                        CrumbleState::Crumbled { elapsed: 0.0 }
+                    } else {
+                        let offset = (elapsed
+                            * constants.crumble_shake_frequency
+                            * std::f32::consts::PI
+                            * 2.0)
+                            .sin()
+                            * constants.crumble_shake_amplitude;
+                        transform.set_translation(rest + Vector3::new(offset, 0.0, 0.0));
+                        CrumbleState::Shaking { elapsed, rest }
+                    }
+                }
+                CrumbleState::Crumbled { elapsed } => {
+                    let elapsed = elapsed + delta_t;
+                    if elapsed >= constants.crumble_respawn_secs {
+                        if let Some(presence) = crumbling.presence.take() {
+                            presences
+                                .insert(entity, presence)
+                                .expect("expected crumbling platform to be alive");
+                        }
+                        if !crumbling.was_hidden {
+                            hiddens.remove(entity);
+                        }
+                        CrumbleState::Solid
+                    } else {
+                        CrumbleState::Crumbled { elapsed }
+                    }
+                }
+            };
+        }
+    }
+}
+
+#[derive(SystemDesc)]
+pub struct TimedPlatformSystem;
+
+impl<'s> System<'s> for TimedPlatformSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, TimedPlatform>,
+        WriteStorage<'s, CollisionPresence>,
+        WriteStorage<'s, Hidden>,
+        Read<'s, Time>,
+        ReadExpect<'s, ConstantsConfig>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut timed, mut presences, mut hiddens, time, constants): Self::SystemData,
+    ) {
+        let beat = (time.absolute_time_seconds() / constants.platforms.timed_beat_secs as f64)
+            .floor() as i64;
+
+        for (entity, timed) in (&entities, &mut timed).join() {
+            // phases can be negative.
+            let solid = (beat + i64::from(timed.phase)).rem_euclid(2) == 0;
+            match (solid, timed.presence.take()) {
+                (true, Some(presence)) => {
+                    presences
+                        .insert(entity, presence)
+                        .expect("expected timed platform to be alive");
+                    if !timed.was_hidden {
+                        hiddens.remove(entity);
+                    }
+                }
+                (false, None) => {
+                    timed.presence = presences.remove(entity);
+                    if timed.presence.is_some() {
+                        timed.was_hidden = hiddens
+                            .insert(entity, Hidden)
+                            .expect("expected timed platform to be alive")
+                            .is_some();
+                    }
+                }
+                (_, presence) => timed.presence = presence,
+            }
+        }
+    }
+}
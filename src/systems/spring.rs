@@ -0,0 +1,42 @@
+use amethyst::{derive::SystemDesc, ecs::prelude::*};
+use log::debug;
+use nalgebra::Vector2;
+
+use crate::{collisions::components::GroundState, world::*};
+
+/// Launches anything which lands on it with a fixed velocity.
+#[derive(Clone)]
+pub struct Spring {
+    pub launch: Vector2<f32>,
+}
+
+impl Component for Spring {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(SystemDesc)]
+pub struct SpringSystem;
+
+impl<'s> System<'s> for SpringSystem {
+    type SystemData = (
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, Player>,
+        ReadStorage<'s, Spring>,
+        ReadStorage<'s, GroundState>,
+    );
+
+    fn run(&mut self, (mut velocities, mut players, springs, ground_states): Self::SystemData) {
+        for (velocity, player, ground) in (&mut velocities, &mut players, &ground_states).join() {
+            let spring = ground.entity.and_then(|entity| springs.get(entity));
+            if let Some(spring) = spring {
+                debug!("launched by spring: {},{}", spring.launch.x, spring.launch.y);
+                // override, rather than add to, whatever the player was doing
+                // so that jumping off a spring doesn't stack with the launch.
+                velocity.intended = spring.launch;
+                // and start the launch with fresh jump state: no air boost
+                // carried over, and no fast fall until down is pressed again.
+                *player = Player::default();
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use amethyst::{core::timing::Time, derive::SystemDesc, ecs::prelude::*, renderer::SpriteRender};
+
+#[derive(Copy, Clone, Debug)]
+pub struct AnimationFrame {
+    pub sprite_number: usize,
+    pub secs: f32,
+}
+
+/// Cycles an entity's `SpriteRender` through a looping list of frames.
+///
+/// Every animation is timed from the same clock, so sprites with the same
+/// frames always show the same one.
+#[derive(Clone, Debug)]
+pub struct AnimatedSprite {
+    frames: Vec<AnimationFrame>,
+    duration: f32,
+}
+
+impl Component for AnimatedSprite {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl AnimatedSprite {
+    /// `None` if the frames don't take any time.
+    pub fn new(frames: Vec<AnimationFrame>) -> Option<Self> {
+        let duration = frames.iter().map(|frame| frame.secs).sum();
+        if duration > 0.0 {
+            Some(AnimatedSprite { frames, duration })
+        } else {
+            None
+        }
+    }
+
+    fn sprite_at(&self, time: f64) -> usize {
+        let mut remaining = (time % f64::from(self.duration)) as f32;
+        for frame in &self.frames {
+            if remaining < frame.secs {
+                return frame.sprite_number;
+            }
+            remaining -= frame.secs;
+        }
+        // only reachable through rounding error.
+        self.frames[self.frames.len() - 1].sprite_number
+    }
+}
+
+#[derive(SystemDesc)]
+pub struct SpriteAnimationSystem;
+
+impl<'s> System<'s> for SpriteAnimationSystem {
+    type SystemData = (
+        WriteStorage<'s, SpriteRender>,
+        ReadStorage<'s, AnimatedSprite>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (mut sprites, animations, time): Self::SystemData) {
+        let now = time.absolute_time_seconds();
+        for (sprite, animation) in (&mut sprites, &animations).join() {
+            sprite.sprite_number = animation.sprite_at(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnimatedSprite, AnimationFrame};
+
+    #[test]
+    fn loops_through_frames() {
+        let frame = |sprite_number, secs| AnimationFrame {
+            sprite_number,
+            secs,
+        };
+        let animation = AnimatedSprite::new(vec![frame(3, 0.25), frame(4, 0.5)]).unwrap();
+        assert_eq!(animation.sprite_at(0.0), 3);
+        assert_eq!(animation.sprite_at(0.3), 4);
+        assert_eq!(animation.sprite_at(0.8), 3);
+        assert_eq!(animation.sprite_at(1.5), 3);
+    }
+
+    #[test]
+    fn needs_some_duration() {
+        let frames = vec![AnimationFrame {
+            sprite_number: 0,
+            secs: 0.0,
+        }];
+        assert!(AnimatedSprite::new(frames).is_none());
+    }
+}
@@ -1,24 +1,61 @@
 use amethyst::{
     assets::Handle,
-    core::Transform,
+    core::{Hidden, Transform},
     prelude::*,
-    renderer::{SpriteRender, SpriteSheet},
+    renderer::{
+        palette::Srgba, resources::Tint, transparent::Transparent, SpriteRender, SpriteSheet,
+    },
 };
 use either::Either;
 use log::debug;
-use nalgebra::{UnitQuaternion, Vector3};
+use nalgebra::{UnitQuaternion, Vector2, Vector3};
 use tmx::{
+    layer::Layer,
     map::{self, Map, TilesetKind},
-    tileset::Tileset,
+    object::Object,
+    property::Property,
+    tileset::{Tile, Tileset},
 };
 
-use crate::{systems::CameraTarget, world};
+use crate::{
+    collisions::layers::CollisionLayer,
+    systems::{
+        AnimatedSprite, AnimationFrame, CameraBounds, CameraRooms, CameraTarget, Collectible,
+        CollectibleKind, Collectibles, CrumblingPlatform, Parallax, Room, RoomTransition, Spring,
+        TimedPlatform,
+    },
+    world,
+};
 use world::MapsConfig;
 use std::path::Path;
 
+mod properties;
+
+use properties::PropertiesExt;
+
 const AIR_TILE_TYPE: &str = "air";
 const PLATFORM_TILE_TYPE: &str = "platform";
 const PLAYER_TILE_TYPE: &str = "player";
+const CRUMBLING_TILE_TYPE: &str = "crumbling";
+const TIMED_TILE_TYPE: &str = "timed";
+const SPRING_TILE_TYPE: &str = "spring";
+const COIN_TILE_TYPE: &str = "coin";
+const GEM_TILE_TYPE: &str = "gem";
+
+const CAMERA_BOUNDS_OBJECT_TYPE: &str = "camera_bounds";
+const CAMERA_TARGET_OBJECT_TYPE: &str = "camera_target";
+const ROOM_OBJECT_TYPE: &str = "room";
+
+/// Layers are drawn in order, spread evenly over this much depth.
+const LAYER_DEPTH_RANGE: f32 = 0.3;
+/// Players are drawn in front of every layer but foreground ones, so this
+/// must be more than `LAYER_DEPTH_RANGE`.
+const PLAYER_DEPTH: f32 = 0.4;
+/// Foreground layers are moved this much further forward.
+const FOREGROUND_DEPTH: f32 = 0.5;
+
+const FOLLOW_CAMERA_MODE: &str = "follow";
+const ROOMS_CAMERA_MODE: &str = "rooms";
 
 pub fn initialize_tiles(world: &mut World, sprite_sheet: Handle<SpriteSheet>) {
     let filepath = world.get_mut::<MapsConfig>().unwrap().default.canonicalize().unwrap();
@@ -32,6 +69,7 @@ pub fn initialize_tiles_with(world: &mut World, sprite_sheet: Handle<SpriteSheet
     assert_eq!(tiles.orientation, map::Orientation::Orthogonal);
     assert_eq!(tiles.render_order, map::RenderOrder::RightDown);
     assert_eq!(tiles.tilesets.len(), 1);
+    world.insert(Collectibles::default());
     let owned_tileset;
     let tileset = match &tiles.tilesets[0].kind {
         TilesetKind::Embedded(tileset) => tileset,
@@ -44,7 +82,13 @@ pub fn initialize_tiles_with(world: &mut World, sprite_sheet: Handle<SpriteSheet
         }
     };
 
-    let tile_iter = tiles.layers.iter().flat_map(|layer| {
+    let layer_settings: Vec<_> = tiles
+        .layers
+        .iter()
+        .enumerate()
+        .map(|(index, layer)| LayerSettings::new(index, tiles.layers.len(), layer))
+        .collect();
+    let tile_iter = tiles.layers.iter().enumerate().flat_map(|(index, layer)| {
         use tmx::layer::LayerData::*;
         match &layer.data {
             Tiles(tiles) => {
@@ -57,12 +101,13 @@ pub fn initialize_tiles_with(world: &mut World, sprite_sheet: Handle<SpriteSheet
                 Either::Left(tiles.iter().enumerate().map(move |(i, v)| {
                     let i = i as i32;
                     (
+                        index,
                         (layer_x + (i % layer_width), layer_y + (i / layer_width)),
                         v,
                     )
                 }))
             }
-            Chunks(chunks) => Either::Right(chunks.iter().flat_map(|chunk| {
+            Chunks(chunks) => Either::Right(chunks.iter().flat_map(move |chunk| {
                 let tmx::layer::Chunk {
                     x: chunk_x,
                     y: chunk_y,
@@ -72,6 +117,7 @@ pub fn initialize_tiles_with(world: &mut World, sprite_sheet: Handle<SpriteSheet
                 chunk.data.iter().enumerate().map(move |(i, v)| {
                     let i = i as i32;
                     (
+                        index,
                         (
                             chunk_x as i32 + (i % (layer_width as i32)),
                             chunk_y as i32 + (i / (layer_width as i32)),
@@ -83,37 +129,472 @@ pub fn initialize_tiles_with(world: &mut World, sprite_sheet: Handle<SpriteSheet
         }
     });
 
-    tile_iter.for_each(|((x, y), tile)| {
-        if tile.gid() == 0 {
+    let first_gid = tiles.tilesets[0].first_gid;
+    // extents of every placed tile, in tile coordinates.
+    let mut tile_extents: Option<((i32, i32), (i32, i32))> = None;
+    tile_iter.for_each(|(index, (x, y), tile)| {
+        let (gid, flip) = TileFlip::from_gid(tile.gid());
+        if gid == 0 {
             return;
         }
+        let settings = &layer_settings[index];
+        // parallax layers aren't where they're drawn, so leave them out.
+        if settings.parallax.is_none() {
+            tile_extents = Some(match tile_extents {
+                Some(((min_x, min_y), (max_x, max_y))) => (
+                    (min_x.min(x), min_y.min(y)),
+                    (max_x.max(x), max_y.max(y)),
+                ),
+                None => ((x, y), (x, y)),
+            });
+        }
         // we simply assume the sprite sheet is our own.
-        let tile = &tileset.tiles[(tile.gid() - tiles.tilesets[0].first_gid) as usize];
-        let sprite_render = SpriteRender {
-            sprite_sheet: sprite_sheet.clone(),
-            sprite_number: tile.id as usize,
-        };
+        let tile = &tileset.tiles[(gid - first_gid) as usize];
         let y = -y;
-        eprintln!(
+        debug!(
             "creating entity {} at {},{} with sprite {}",
             tile.r#type, x, y, tile.id
         );
-        let entity = match &*tile.r#type {
-            AIR_TILE_TYPE => world.create_entity(),
-            PLATFORM_TILE_TYPE => world::create_platform(world),
-            PLAYER_TILE_TYPE => world::create_player(world).with(CameraTarget {
-                offset: Vector3::new(0.0, 0.0, 1.0),
-                target_rotation: UnitQuaternion::identity(),
+        let mut translation = Vector3::new(x as f32 * 16.0, y as f32 * 16.0, settings.depth);
+        // parallax and non-colliding layers are scenery, whatever their tiles'
+        // types.
+        let entity = match settings.parallax {
+            Some(factor) => world.create_entity().with(Parallax {
+                factor,
+                origin: translation,
             }),
-            other => panic!("unknown tile type {:?}", other),
+            None if !settings.collision => world.create_entity(),
+            None => {
+                translation.z = entity_depth(&tile.r#type, settings.depth);
+                create_tile_entity(world, &tile.r#type, &[&tile.properties])
+            }
         };
-        entity
-            .with(
-                Transform::default()
-                    .append_translation([x as f32 * 16.0, y as f32 * 16.0, 0.0].into())
-                    .clone(),
-            )
-            .with(sprite_render)
+        let mut transform = Transform::default();
+        transform.set_translation(translation);
+        flip.apply(&mut transform);
+        animate(settings.style(entity), tile)
+            .with(transform)
+            .with(tile_sprite(&sprite_sheet, tile))
             .build();
     });
+
+    let mut camera_bounds = tile_extents.map(|((min_x, min_y), (max_x, max_y))| {
+        let (min_x, max_y) = tiled_to_world(min_x as f32 * 16.0, min_y as f32 * 16.0);
+        let (max_x, min_y) = tiled_to_world(max_x as f32 * 16.0 + 16.0, max_y as f32 * 16.0 + 16.0);
+        CameraBounds {
+            min: Vector2::new(min_x, min_y),
+            max: Vector2::new(max_x, max_y),
+        }
+    });
+
+    let mut rooms = Vec::new();
+
+    // tile objects, which can override their tile's properties individually.
+    for object in tiles.object_groups.iter().flat_map(|group| &group.objects) {
+        if object.r#type == CAMERA_BOUNDS_OBJECT_TYPE {
+            camera_bounds = Some(object_bounds(object));
+            continue;
+        }
+        if object.r#type == ROOM_OBJECT_TYPE {
+            rooms.push(room(object, &tiles.properties));
+            continue;
+        }
+        if object.r#type == CAMERA_TARGET_OBJECT_TYPE {
+            // a point of interest for cameras to keep in view.
+            let (x, y) = tiled_to_world(
+                object.x + object.width / 2.0,
+                object.y + object.height / 2.0,
+            );
+            world
+                .create_entity()
+                .with(
+                    Transform::default()
+                        .append_translation([x, y, 0.0].into())
+                        .clone(),
+                )
+                .with(camera_target(&[&object.properties]))
+                .build();
+            continue;
+        }
+        let (gid, flip) = match object.gid.map(TileFlip::from_gid) {
+            Some((gid, flip)) if gid != 0 => (gid, flip),
+            _ => continue,
+        };
+        let tile = &tileset.tiles[(gid - first_gid) as usize];
+        let tile_type = if object.r#type.is_empty() {
+            &tile.r#type
+        } else {
+            &object.r#type
+        };
+        // tile objects are anchored at their bottom left corner, and rotate
+        // clockwise (in degrees) around it.
+        let angle = object.rotation.to_radians();
+        let (sin, cos) = angle.sin_cos();
+        let (half_width, half_height) = (object.width / 2.0, object.height / 2.0);
+        let (x, y) = tiled_to_world(
+            object.x + cos * half_width + sin * half_height,
+            object.y + sin * half_width - cos * half_height,
+        );
+        debug!("creating object {} at {},{}", tile_type, x, y);
+        let mut transform = Transform::default();
+        transform
+            .set_translation_xyz(x, y, entity_depth(tile_type, 0.0))
+            .set_rotation_2d(-angle)
+            .set_scale([object.width / 16.0, object.height / 16.0, 1.0].into());
+        flip.apply(&mut transform);
+        let entity = create_tile_entity(world, tile_type, &[&object.properties, &tile.properties]);
+        animate(entity, tile)
+            .with(transform)
+            .with(tile_sprite(&sprite_sheet, tile))
+            .build();
+    }
+
+    match camera_bounds {
+        Some(bounds) => world.insert(bounds),
+        None => {
+            world.remove::<CameraBounds>();
+        }
+    }
+    match tiles.properties.str_property("camera_mode") {
+        None | Some(FOLLOW_CAMERA_MODE) => {
+            world.remove::<CameraRooms>();
+        }
+        Some(ROOMS_CAMERA_MODE) => world.insert(CameraRooms::new(rooms)),
+        Some(other) => panic!("unknown camera mode {:?}", other),
+    }
+}
+
+/// A `CameraTarget` weighted by the `camera_weight` property.
+fn camera_target(properties: &[&[Property]]) -> CameraTarget {
+    CameraTarget {
+        offset: Vector3::new(0.0, 0.0, 1.0),
+        target_rotation: UnitQuaternion::identity(),
+        weight: properties.f32_property("camera_weight").unwrap_or(1.0),
+    }
+}
+
+/// The world-space rectangle covered by a rectangle object.
+fn object_bounds(object: &Object) -> CameraBounds {
+    let (min_x, max_y) = tiled_to_world(object.x, object.y);
+    let (max_x, min_y) = tiled_to_world(object.x + object.width, object.y + object.height);
+    CameraBounds {
+        min: Vector2::new(min_x, min_y),
+        max: Vector2::new(max_x, max_y),
+    }
+}
+
+/// How a tile layer's tiles are created and drawn.
+struct LayerSettings {
+    depth: f32,
+    parallax: Option<Vector2<f32>>,
+    /// Whether the layer's tiles act on their type, rather than only being
+    /// drawn.
+    collision: bool,
+    /// Hidden layers are still created, so they can hold invisible walls.
+    visible: bool,
+    opacity: f32,
+}
+
+impl LayerSettings {
+    /// Settings for the `index`th of `layer_count` layers, from the bottom.
+    fn new(index: usize, layer_count: usize, layer: &Layer) -> Self {
+        let mut depth = index as f32 / layer_count as f32 * LAYER_DEPTH_RANGE;
+        if layer.properties.bool_property("foreground").unwrap_or(false) {
+            depth += FOREGROUND_DEPTH;
+        }
+        LayerSettings {
+            depth,
+            parallax: layer_parallax(&layer.properties),
+            collision: layer.properties.bool_property("collision").unwrap_or(true),
+            visible: layer.visible,
+            opacity: layer.opacity,
+        }
+    }
+
+    fn style<'a>(&self, entity: EntityBuilder<'a>) -> EntityBuilder<'a> {
+        let entity = if self.visible {
+            entity
+        } else {
+            entity.with(Hidden)
+        };
+        if self.opacity < 1.0 {
+            entity
+                .with(Tint(Srgba::new(1.0, 1.0, 1.0, self.opacity)))
+                .with(Transparent)
+        } else {
+            entity
+        }
+    }
+}
+
+/// Depth of an entity created from a tile on a layer at `layer_depth`.
+fn entity_depth(tile_type: &str, layer_depth: f32) -> f32 {
+    if tile_type == PLAYER_TILE_TYPE {
+        PLAYER_DEPTH
+    } else {
+        layer_depth
+    }
+}
+
+/// How fast a layer scrolls, from its `parallax` property or its
+/// `parallax_x` and `parallax_y` ones, named after Tiled's own parallax
+/// factors. `None` for layers which move with the world.
+fn layer_parallax(properties: &[Property]) -> Option<Vector2<f32>> {
+    let both = properties.f32_property("parallax");
+    let factor = Vector2::new(
+        properties.f32_property("parallax_x").or(both).unwrap_or(1.0),
+        properties.f32_property("parallax_y").or(both).unwrap_or(1.0),
+    );
+    if factor == Vector2::new(1.0, 1.0) {
+        None
+    } else {
+        Some(factor)
+    }
+}
+
+/// A `Room` covering a rectangle object, which transitions as its own or
+/// the map's `room_transition` property says.
+fn room(object: &Object, map_properties: &[Property]) -> Room {
+    let properties: &[&[Property]] = &[&object.properties, map_properties];
+    let transition = match properties.str_property("room_transition") {
+        Some(name) => RoomTransition::from_name(name)
+            .unwrap_or_else(|| panic!("unknown room transition {:?}", name)),
+        None => RoomTransition::Slide,
+    };
+    Room {
+        bounds: object_bounds(object),
+        transition,
+    }
+}
+
+const FLIPPED_HORIZONTALLY_FLAG: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY_FLAG: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY_FLAG: u32 = 0x2000_0000;
+
+/// How a placed tile is mirrored, from the top bits of its gid.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct TileFlip {
+    horizontal: bool,
+    vertical: bool,
+    /// Swaps the tile's x and y axes, before the other flips.
+    diagonal: bool,
+}
+
+impl TileFlip {
+    /// Splits a gid into the tile's actual gid and how it's flipped.
+    fn from_gid(gid: u32) -> (u32, TileFlip) {
+        let flags = FLIPPED_HORIZONTALLY_FLAG | FLIPPED_VERTICALLY_FLAG | FLIPPED_DIAGONALLY_FLAG;
+        let flip = TileFlip {
+            horizontal: gid & FLIPPED_HORIZONTALLY_FLAG != 0,
+            vertical: gid & FLIPPED_VERTICALLY_FLAG != 0,
+            diagonal: gid & FLIPPED_DIAGONALLY_FLAG != 0,
+        };
+        (gid & !flags, flip)
+    }
+
+    /// The rotation, in radians, and then scale which mirror a tile the way
+    /// Tiled does.
+    fn rotation_and_scale(self) -> (f32, Vector2<f32>) {
+        let sign = |flipped| if flipped { -1.0 } else { 1.0 };
+        let (h, v) = (sign(self.horizontal), sign(self.vertical));
+        if self.diagonal {
+            // with y pointing up, swapping the axes is a quarter turn of a
+            // horizontally mirrored tile.
+            (std::f32::consts::FRAC_PI_2, Vector2::new(-v, h))
+        } else {
+            (0.0, Vector2::new(h, v))
+        }
+    }
+
+    /// Mirrors a tile's transform, and so its sprite and collider too.
+    ///
+    /// The tile is flipped before being stretched by the transform's scale,
+    /// as Tiled does for tile objects.
+    fn apply(self, transform: &mut Transform) {
+        let (angle, scale) = self.rotation_and_scale();
+        if angle != 0.0 {
+            transform.append_rotation_z_axis(angle);
+        }
+        let transform_scale = transform.scale_mut();
+        if self.diagonal {
+            // the quarter turn comes before the scale, so the scale has to
+            // stretch the turned tile's axes instead.
+            std::mem::swap(&mut transform_scale.x, &mut transform_scale.y);
+        }
+        transform_scale.x *= scale.x;
+        transform_scale.y *= scale.y;
+    }
+}
+
+/// Converts a position in Tiled pixel coordinates into world coordinates,
+/// where tile (0, 0) is centered on the origin and y points up.
+fn tiled_to_world(x: f32, y: f32) -> (f32, f32) {
+    (x - 8.0, 8.0 - y)
+}
+
+fn tile_sprite(sprite_sheet: &Handle<SpriteSheet>, tile: &Tile) -> SpriteRender {
+    SpriteRender {
+        sprite_sheet: sprite_sheet.clone(),
+        sprite_number: tile.id as usize,
+    }
+}
+
+/// Adds the tile's frame animation from its tileset, if it has one.
+fn animate<'a>(entity: EntityBuilder<'a>, tile: &Tile) -> EntityBuilder<'a> {
+    let frames = tile
+        .animation
+        .iter()
+        .map(|frame| AnimationFrame {
+            sprite_number: frame.tile_id as usize,
+            // durations are in milliseconds.
+            secs: frame.duration as f32 / 1000.0,
+        })
+        .collect();
+    match AnimatedSprite::new(frames) {
+        Some(animation) => entity.with(animation),
+        None => entity,
+    }
+}
+
+fn create_tile_entity<'a>(
+    world: &'a mut World,
+    tile_type: &str,
+    properties: &[&[Property]],
+) -> EntityBuilder<'a> {
+    let layer = |default| match properties.str_property("collision_layer") {
+        Some(name) => CollisionLayer::from_name(name)
+            .unwrap_or_else(|| panic!("unknown collision layer {:?}", name)),
+        None => default,
+    };
+    let terrain = layer(CollisionLayer::Terrain);
+    let entity = match tile_type {
+        AIR_TILE_TYPE => world.create_entity(),
+        PLATFORM_TILE_TYPE => world::create_platform(world, terrain),
+        PLAYER_TILE_TYPE => world::create_player(world).with(camera_target(properties)),
+        CRUMBLING_TILE_TYPE => {
+            world::create_platform(world, terrain).with(CrumblingPlatform::default())
+        }
+        TIMED_TILE_TYPE => world::create_platform(world, terrain).with(TimedPlatform::new(
+            properties.i32_property("phase").unwrap_or(0),
+        )),
+        SPRING_TILE_TYPE => {
+            let launch = Vector2::new(
+                properties.f32_property("launch_x").unwrap_or(0.0),
+                properties
+                    .f32_property("launch_y")
+                    .unwrap_or_else(|| panic!("spring is missing a launch_y property")),
+            );
+            world::create_platform(world, terrain).with(Spring { launch })
+        }
+        COIN_TILE_TYPE => {
+            create_collectible(world, layer(CollisionLayer::Pickup), CollectibleKind::Coin)
+        }
+        GEM_TILE_TYPE => {
+            create_collectible(world, layer(CollisionLayer::Pickup), CollectibleKind::Gem)
+        }
+        other => panic!("unknown tile type {:?}", other),
+    };
+    match surface(properties) {
+        Some(surface) => entity.with(surface),
+        None => entity,
+    }
+}
+
+fn surface(properties: &[&[Property]]) -> Option<world::Surface> {
+    const SURFACE_PROPERTIES: &[&str] = &["surface", "friction", "acceleration", "push"];
+    if SURFACE_PROPERTIES
+        .iter()
+        .all(|name| properties.property(name).is_none())
+    {
+        return None;
+    }
+    let default = world::Surface::default();
+    Some(world::Surface {
+        kind: properties
+            .str_property("surface")
+            .map_or(default.kind, str::to_owned),
+        friction: properties.f32_property("friction").unwrap_or(default.friction),
+        acceleration: properties
+            .f32_property("acceleration")
+            .unwrap_or(default.acceleration),
+        push: properties.f32_property("push").unwrap_or(default.push),
+    })
+}
+
+fn create_collectible(
+    world: &mut World,
+    layer: CollisionLayer,
+    kind: CollectibleKind,
+) -> EntityBuilder {
+    world.write_resource::<Collectibles>().total += 1;
+    world::create_pickup(world, layer).with(Collectible { kind })
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::core::Transform;
+    use nalgebra::{Matrix2, Rotation2, Vector2, U2};
+
+    use super::TileFlip;
+
+    #[test]
+    fn splits_flags_from_gid() {
+        let (gid, flip) = TileFlip::from_gid(0xa000_0005);
+        assert_eq!(gid, 5);
+        assert_eq!(
+            flip,
+            TileFlip {
+                horizontal: true,
+                vertical: false,
+                diagonal: true,
+            }
+        );
+        assert_eq!(TileFlip::from_gid(5), (5, TileFlip::default()));
+    }
+
+    /// The linear part of a flipped tile's transform, applied to world
+    /// coordinates.
+    fn matrix(flip: TileFlip) -> Matrix2<f32> {
+        let (angle, scale) = flip.rotation_and_scale();
+        let matrix = Rotation2::new(angle).matrix() * Matrix2::from_diagonal(&scale);
+        matrix.map(f32::round)
+    }
+
+    #[test]
+    fn flips_like_tiled() {
+        // Tiled swaps the axes with y pointing down, which maps (x, y) to
+        // (-y, -x) with y pointing up.
+        let diagonal = TileFlip {
+            diagonal: true,
+            ..TileFlip::default()
+        };
+        assert_eq!(matrix(diagonal), Matrix2::new(0.0, -1.0, -1.0, 0.0));
+        // and flips horizontally afterwards.
+        let diagonal_horizontal = TileFlip {
+            horizontal: true,
+            ..diagonal
+        };
+        assert_eq!(matrix(diagonal_horizontal), Matrix2::new(0.0, 1.0, -1.0, 0.0));
+        let vertical = TileFlip {
+            vertical: true,
+            ..TileFlip::default()
+        };
+        assert_eq!(matrix(vertical), Matrix2::from_diagonal(&Vector2::new(1.0, -1.0)));
+    }
+
+    #[test]
+    fn flips_objects_before_scaling_them() {
+        // a 32x16 tile object, whose tile is flipped before being stretched
+        // to fit.
+        let flip = TileFlip {
+            diagonal: true,
+            vertical: true,
+            ..TileFlip::default()
+        };
+        let mut transform = Transform::default();
+        transform.set_scale([2.0, 1.0, 1.0].into());
+        flip.apply(&mut transform);
+        let linear = transform.matrix().fixed_slice::<U2, U2>(0, 0).map(f32::round);
+        assert_eq!(linear, Matrix2::new(2.0, 0.0, 0.0, 1.0) * matrix(flip));
+    }
 }
@@ -0,0 +1,48 @@
+use tmx::property::{Property, PropertyValue};
+
+/// Typed lookups for Tiled custom properties.
+pub trait PropertiesExt {
+    fn property(&self, name: &str) -> Option<&PropertyValue>;
+
+    fn f32_property(&self, name: &str) -> Option<f32> {
+        match self.property(name)? {
+            PropertyValue::Float(v) => Some(*v as f32),
+            PropertyValue::Int(v) => Some(*v as f32),
+            other => panic!("expected number for property {:?}, found {:?}", name, other),
+        }
+    }
+
+    fn i32_property(&self, name: &str) -> Option<i32> {
+        match self.property(name)? {
+            PropertyValue::Int(v) => Some(*v as i32),
+            other => panic!("expected int for property {:?}, found {:?}", name, other),
+        }
+    }
+
+    fn bool_property(&self, name: &str) -> Option<bool> {
+        match self.property(name)? {
+            PropertyValue::Bool(v) => Some(*v),
+            other => panic!("expected bool for property {:?}, found {:?}", name, other),
+        }
+    }
+
+    fn str_property(&self, name: &str) -> Option<&str> {
+        match self.property(name)? {
+            PropertyValue::String(v) => Some(v),
+            other => panic!("expected string for property {:?}, found {:?}", name, other),
+        }
+    }
+}
+
+impl PropertiesExt for [Property] {
+    fn property(&self, name: &str) -> Option<&PropertyValue> {
+        self.iter().find(|p| p.name == name).map(|p| &p.value)
+    }
+}
+
+/// Layered lookup, where earlier property lists take precedence.
+impl PropertiesExt for [&[Property]] {
+    fn property(&self, name: &str) -> Option<&PropertyValue> {
+        self.iter().find_map(|properties| properties.property(name))
+    }
+}
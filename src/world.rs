@@ -1,20 +1,20 @@
 use amethyst::ecs::prelude::*;
 use nalgebra::Vector2;
-use ncollide2d::{
-    pipeline::{CollisionGroups, GeometricQueryType},
-    shape::{Cuboid, ShapeHandle},
-};
 use specs_derive::Component;
 
 use crate::{
-    collisions::components::{CollisionPresence, HasGravity},
-    systems::{CameraFollowConstants, PlayerMovementConstants},
+    collisions::{
+        components::{CollisionPresence, GroundState, HasGravity},
+        geometry::{CollisionShape, QueryType},
+        layers::{CollisionLayer, CollisionLayersConfig},
+    },
+    systems::{
+        CameraFollowConstants, CameraRoomsConstants, CameraShakeConstants, FastFallConstants,
+        PixelPerfectConstants, PlatformConstants, PlayerMovementConstants,
+    },
 };
 use std::path::PathBuf;
 
-const PLATFORM_COLLISION_GROUP: usize = 1;
-const PLAYER_COLLISION_GROUP: usize = 2;
-
 // pub const PLAYER_JUMP: f32 = 300.0;
 // pub const GRAVITY_ACCEL: f32 = 15.0;
 
@@ -22,7 +22,18 @@ const PLAYER_COLLISION_GROUP: usize = 2;
 pub struct ConstantsConfig {
     pub player: PlayerMovementConstants,
     pub gravity_accel: f32,
+    pub terminal_velocity: f32,
+    pub fast_fall: FastFallConstants,
     pub camera_follow: CameraFollowConstants,
+    pub camera_rooms: CameraRoomsConstants,
+    pub camera_shake: CameraShakeConstants,
+    pub pixel_perfect: PixelPerfectConstants,
+    pub platforms: PlatformConstants,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct AccessibilityConfig {
+    pub screen_shake: bool,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -34,10 +45,37 @@ pub struct MapsConfig {
 #[storage(NullStorage)]
 pub struct Platform;
 
+/// Material a tile provides to whatever stands on it.
+#[derive(Clone, Debug, Component)]
+#[storage(DenseVecStorage)]
+pub struct Surface {
+    pub kind: String,
+    /// Multiplier on how quickly horizontal speed decays. Ice is below 1.0,
+    /// mud above.
+    pub friction: f32,
+    /// Multiplier on horizontal acceleration from input.
+    pub acceleration: f32,
+    /// Horizontal speed the surface carries things standing on it at, for
+    /// conveyors.
+    pub push: f32,
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Surface {
+            kind: "default".to_owned(),
+            friction: 1.0,
+            acceleration: 1.0,
+            push: 0.0,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Component, Default)]
 #[storage(DenseVecStorage)]
 pub struct Player {
     pub air_boost: f32,
+    pub fast_falling: bool,
 }
 
 #[derive(Copy, Clone, Component)]
@@ -53,34 +91,38 @@ impl Default for Velocity {
     }
 }
 
-pub fn create_platform(world: &mut World) -> EntityBuilder {
-    let mut collision_groups = CollisionGroups::new()
-        .with_membership(&[PLATFORM_COLLISION_GROUP])
-        .with_whitelist(&[PLAYER_COLLISION_GROUP]);
-    collision_groups.disable_self_interaction();
+pub fn create_platform(world: &mut World, layer: CollisionLayer) -> EntityBuilder {
+    let collision_groups = world.read_resource::<CollisionLayersConfig>().groups(layer);
     world
         .create_entity()
         .with(Platform)
         .with(CollisionPresence {
-            shape: ShapeHandle::new(Cuboid::new([8.0, 8.0].into())),
+            shape: CollisionShape::cuboid(Vector2::new(8.0, 8.0)),
             collision_groups,
-            query_type: GeometricQueryType::Contacts(0.0, 0.0),
+            query_type: QueryType::Contacts { prediction: 0.0 },
         })
 }
 
 pub fn create_player(world: &mut World) -> EntityBuilder {
-    let mut collision_groups = CollisionGroups::new()
-        .with_membership(&[PLAYER_COLLISION_GROUP])
-        .with_whitelist(&[PLATFORM_COLLISION_GROUP]);
-    collision_groups.disable_self_interaction();
+    let collision_groups =
+        world.read_resource::<CollisionLayersConfig>().groups(CollisionLayer::Player);
     world
         .create_entity()
         .with(Player::default())
         .with(CollisionPresence {
-            shape: ShapeHandle::new(Cuboid::new([8.0, 8.0].into())),
+            shape: CollisionShape::cuboid(Vector2::new(8.0, 8.0)),
             collision_groups,
-            query_type: GeometricQueryType::Contacts(20.0, 0.0),
+            query_type: QueryType::Contacts { prediction: 20.0 },
         })
-        .with(HasGravity)
+        .with(HasGravity::default())
+        .with(GroundState::default())
         .with(Velocity::default())
 }
+
+pub fn create_pickup(world: &mut World, layer: CollisionLayer) -> EntityBuilder {
+    let collision_groups = world.read_resource::<CollisionLayersConfig>().groups(layer);
+    world.create_entity().with(CollisionPresence::sensor(
+        CollisionShape::cuboid(Vector2::new(6.0, 6.0)),
+        collision_groups,
+    ))
+}